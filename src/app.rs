@@ -76,6 +76,8 @@ pub enum RyvmCommand {
     Samples,
     #[structopt(about = "Open the loops folder")]
     Loops,
+    #[structopt(about = "Open the tunings folder")]
+    Tunings,
     #[structopt(about = "List all available audio input nodes")]
     Inputs,
     #[structopt(about = "Manage audio outputs")]
@@ -4,6 +4,8 @@ use std::{
     ops::{Add, AddAssign, Mul},
 };
 
+use itertools::Either;
+
 use crate::{
     node::Node,
     state::State,
@@ -92,6 +94,22 @@ impl Channel {
             self.nodes.remove(name);
         }
     }
+    /// Check if this channel is a bus that other channels can send to
+    #[must_use]
+    pub fn is_bus(&self) -> bool {
+        self.nodes().any(|node| matches!(node, Node::BusReturn(_)))
+    }
+    /// Run this channel's control processors in order
+    pub fn process_controls(&self, channel_num: u8, state: &State, cache: &mut FrameCache) {
+        let mut processors: Vec<(&Name, &Node)> = self
+            .names_nodes()
+            .filter(|(_, node)| node.control_order().is_some())
+            .collect();
+        processors.sort_by_key(|(name, node)| (node.control_order(), **name));
+        for (_, node) in processors {
+            node.process_controls(channel_num, state, cache);
+        }
+    }
     #[must_use]
     pub fn next_from(
         &self,
@@ -100,20 +118,29 @@ impl Channel {
         state: &State,
         cache: &mut FrameCache,
     ) -> Voice {
+        // Resolve references to nodes in other channels
+        if let Some((other_num, name)) = utility::split_node_ref(name) {
+            return if other_num == channel_num {
+                self.next_from(channel_num, name, state, cache)
+            } else if let Some(other) = state.channel(other_num) {
+                other.next_from(other_num, name, state, cache)
+            } else {
+                Voice::SILENT
+            };
+        }
         let full_name = (channel_num, utility::name_from_str(name));
-        if cache.visited.contains(&full_name) {
+        if let Some(voice) = cache.voices.get(&full_name) {
+            // Nodes read by more than one other node are only processed once
+            *voice
+        } else if cache.visited.contains(&full_name) {
             // Avoid infinite loops
             Voice::mono(0.0)
         } else {
             cache.visited.insert(full_name.clone());
             if let Some(node) = self.get(name) {
-                if let Some(voice) = cache.voices.get(&full_name) {
-                    *voice
-                } else {
-                    let voice = node.next(channel_num, self, state, cache, name);
-                    cache.voices.insert(full_name, voice);
-                    voice
-                }
+                let voice = node.next(channel_num, self, state, cache, name);
+                cache.voices.insert(full_name, voice);
+                voice
             } else {
                 Voice::SILENT
             }
@@ -127,6 +154,10 @@ pub struct FrameCache {
     pub audio_input: HashMap<Name, Voice>,
     pub visited: HashSet<(u8, Name)>,
     pub from_loop: bool,
+    /// Controls for channels whose controls have been processed
+    pub processed_controls: HashMap<u8, Vec<Control>>,
+    /// The sum of the voices sent to each bus channel
+    pub sends: HashMap<u8, Voice>,
 }
 
 impl FrameCache {
@@ -134,10 +165,20 @@ impl FrameCache {
     //     self.controls.values().flat_map(|v| v.iter().copied())
     // }
     pub fn channel_controls(&self, channel: u8) -> impl Iterator<Item = Control> + '_ {
-        self.controls
-            .iter()
-            .filter(move |((_, ch), _)| ch == &channel)
-            .flat_map(|(_, controls)| controls.iter().copied())
+        if let Some(controls) = self.processed_controls.get(&channel) {
+            Either::Left(controls.iter().copied())
+        } else {
+            Either::Right(
+                self.controls
+                    .iter()
+                    .filter(move |((_, ch), _)| ch == &channel)
+                    .flat_map(|(_, controls)| controls.iter().copied()),
+            )
+        }
+    }
+    /// Replace the controls of a channel with processed ones
+    pub fn set_channel_controls(&mut self, channel: u8, controls: Vec<Control>) {
+        self.processed_controls.insert(channel, controls);
     }
 }
 
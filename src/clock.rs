@@ -0,0 +1,92 @@
+use rand::random;
+
+use crate::{state::State, ty::Control};
+
+/// A clock that divides time into steps
+///
+/// If a division is given and there is a loop master, steps are synced to the loop master.
+/// Otherwise, steps are a fixed number of seconds long, scaled by the tempo.
+#[derive(Debug, Clone, Default)]
+pub struct StepClock {
+    t: Option<f32>,
+    count: u64,
+}
+
+impl StepClock {
+    /// Advance the clock by one frame
+    ///
+    /// Returns the length of a step in frames and the index of the step
+    /// that starts during this frame, if any
+    pub fn tick(
+        &mut self,
+        state: &State,
+        step: f32,
+        division: Option<u16>,
+        swing: f32,
+    ) -> (f32, Option<u64>) {
+        let synced = match (division, state.loop_master, state.loop_master_t()) {
+            (Some(division), Some(master), Some(t)) if division > 0 => {
+                Some((master.period / f32::from(division), t))
+            }
+            _ => None,
+        };
+        let (len, now) = synced.unwrap_or_else(|| {
+            let len = step * state.vars.sample_rate as f32;
+            // Wrap every two steps so that the time stays precise
+            let mut now = self.t.map_or(0.0, |t| t + state.vars.tempo);
+            if now >= 2.0 * len {
+                now -= 2.0 * len;
+            }
+            (len, now)
+        });
+        // Start over if the clock wrapped around
+        let prev = self.t.filter(|&prev| prev <= now).unwrap_or(-1.0);
+        self.t = Some(now);
+        if len <= 0.0 {
+            return (len, None);
+        }
+        // Every odd step is delayed by the swing
+        let swing = swing.max(0.0).min(1.0) * len / 2.0;
+        let current = (now / len).floor().max(0.0) as u64;
+        let started = (current.saturating_sub(1)..=current).find(|&i| {
+            let start = i as f32 * len + if i % 2 == 1 { swing } else { 0.0 };
+            prev < start && start <= now
+        });
+        let index = started.map(|i| {
+            if synced.is_some() {
+                i
+            } else {
+                self.count += 1;
+                self.count - 1
+            }
+        });
+        (len, index)
+    }
+}
+
+/// Notes that end after a set amount of time
+#[derive(Debug, Clone, Default)]
+pub struct NoteGates {
+    /// Remaining frames, id, and note of each held note
+    held: Vec<(f32, u64, u8)>,
+}
+
+impl NoteGates {
+    /// Start a note that ends after some number of frames
+    pub fn start(&mut self, frames: f32, note: u8, velocity: u8, controls: &mut Vec<Control>) {
+        let id = random();
+        controls.push(Control::NoteStart(id, note, velocity));
+        self.held.push((frames, id, note));
+    }
+    /// Advance by one frame and end notes whose time is up
+    pub fn tick(&mut self, tempo: f32, controls: &mut Vec<Control>) {
+        for i in (0..self.held.len()).rev() {
+            let (remaining, id, note) = &mut self.held[i];
+            *remaining -= tempo;
+            if *remaining <= 0.0 {
+                controls.push(Control::NoteEnd(*id, *note));
+                self.held.remove(i);
+            }
+        }
+    }
+}
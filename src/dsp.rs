@@ -0,0 +1,541 @@
+use std::{
+    collections::VecDeque,
+    f32::consts::PI,
+    ops::{Add, Mul},
+};
+
+use crate::{spec::DistortionCurve, ty::Voice};
+
+/// A delay line that can be read at fractional delays
+#[derive(Debug, Clone, Default)]
+pub struct DelayLine<T> {
+    buffer: Vec<T>,
+    /// The index of the next write
+    index: usize,
+}
+
+impl<T> DelayLine<T>
+where
+    T: Copy + Default + Add<Output = T> + Mul<f32, Output = T>,
+{
+    /// Create a new delay line that can delay up to `capacity` frames
+    pub fn new(capacity: usize) -> Self {
+        DelayLine {
+            buffer: vec![T::default(); capacity.max(2)],
+            index: 0,
+        }
+    }
+    /// Get the maximum delay in frames
+    pub fn capacity(&self) -> usize {
+        self.buffer.len()
+    }
+    /// Push a new value into the line
+    pub fn push(&mut self, value: T) {
+        self.buffer[self.index] = value;
+        self.index = (self.index + 1) % self.buffer.len();
+    }
+    /// Get the value that was pushed `delay` frames ago
+    ///
+    /// A delay of 1 is the most recently pushed value.
+    /// Fractional delays are linearly interpolated.
+    pub fn read(&self, delay: f32) -> T {
+        let delay = delay.max(1.0).min(self.buffer.len() as f32);
+        let whole = delay.floor();
+        let frac = delay - whole;
+        let a = self.get(whole as usize);
+        if frac == 0.0 {
+            a
+        } else {
+            a * (1.0 - frac) + self.get(whole as usize + 1) * frac
+        }
+    }
+    fn get(&self, delay: usize) -> T {
+        let len = self.buffer.len();
+        let delay = delay.min(len);
+        self.buffer[(self.index + len - delay) % len]
+    }
+}
+
+/// Mix a dry and a wet signal
+///
+/// At 0, only the dry signal is output. At 1, only the wet signal is output.
+/// At 0.5, both are output at full volume.
+pub fn mix(dry: Voice, wet: Voice, mix: f32) -> Voice {
+    let mix = mix.max(0.0).min(1.0);
+    dry * (2.0 * (1.0 - mix)).min(1.0) + wet * (2.0 * mix).min(1.0)
+}
+
+/// The shape of a biquad filter
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[allow(missing_docs)]
+pub enum BiquadShape {
+    LowPass,
+    HighPass,
+    BandPass,
+    Notch,
+    Peak,
+    LowShelf,
+    HighShelf,
+}
+
+/// The normalized coefficients of a biquad filter
+#[derive(Debug, Clone, Copy, Default)]
+pub struct BiquadCoefficients {
+    b0: f32,
+    b1: f32,
+    b2: f32,
+    a1: f32,
+    a2: f32,
+}
+
+impl BiquadCoefficients {
+    /// Calculate the coefficients for a filter
+    ///
+    /// `gain` is in decibels and only affects peak and shelf filters.
+    /// The formulas are from Robert Bristow-Johnson's Audio EQ Cookbook.
+    pub fn new(shape: BiquadShape, sample_rate: u32, freq: f32, q: f32, gain: f32) -> Self {
+        let sample_rate = sample_rate as f32;
+        let freq = freq.max(1.0).min(sample_rate * 0.49);
+        let w0 = 2.0 * PI * freq / sample_rate;
+        let (sin, cos) = w0.sin_cos();
+        let alpha = sin / (2.0 * q.max(0.01));
+        let a = 10_f32.powf(gain / 40.0);
+        let shelf = 2.0 * a.sqrt() * alpha;
+        let (b0, b1, b2, a0, a1, a2) = match shape {
+            BiquadShape::LowPass => (
+                (1.0 - cos) / 2.0,
+                1.0 - cos,
+                (1.0 - cos) / 2.0,
+                1.0 + alpha,
+                -2.0 * cos,
+                1.0 - alpha,
+            ),
+            BiquadShape::HighPass => (
+                (1.0 + cos) / 2.0,
+                -(1.0 + cos),
+                (1.0 + cos) / 2.0,
+                1.0 + alpha,
+                -2.0 * cos,
+                1.0 - alpha,
+            ),
+            BiquadShape::BandPass => (alpha, 0.0, -alpha, 1.0 + alpha, -2.0 * cos, 1.0 - alpha),
+            BiquadShape::Notch => (1.0, -2.0 * cos, 1.0, 1.0 + alpha, -2.0 * cos, 1.0 - alpha),
+            BiquadShape::Peak => (
+                1.0 + alpha * a,
+                -2.0 * cos,
+                1.0 - alpha * a,
+                1.0 + alpha / a,
+                -2.0 * cos,
+                1.0 - alpha / a,
+            ),
+            BiquadShape::LowShelf => (
+                a * ((a + 1.0) - (a - 1.0) * cos + shelf),
+                2.0 * a * ((a - 1.0) - (a + 1.0) * cos),
+                a * ((a + 1.0) - (a - 1.0) * cos - shelf),
+                (a + 1.0) + (a - 1.0) * cos + shelf,
+                -2.0 * ((a - 1.0) + (a + 1.0) * cos),
+                (a + 1.0) + (a - 1.0) * cos - shelf,
+            ),
+            BiquadShape::HighShelf => (
+                a * ((a + 1.0) + (a - 1.0) * cos + shelf),
+                -2.0 * a * ((a - 1.0) + (a + 1.0) * cos),
+                a * ((a + 1.0) + (a - 1.0) * cos - shelf),
+                (a + 1.0) - (a - 1.0) * cos + shelf,
+                2.0 * ((a - 1.0) - (a + 1.0) * cos),
+                (a + 1.0) - (a - 1.0) * cos - shelf,
+            ),
+        };
+        BiquadCoefficients {
+            b0: b0 / a0,
+            b1: b1 / a0,
+            b2: b2 / a0,
+            a1: a1 / a0,
+            a2: a2 / a0,
+        }
+    }
+}
+
+/// The state of a biquad filter for a single channel
+#[derive(Debug, Clone, Copy, Default)]
+struct BiquadChannel {
+    z1: f32,
+    z2: f32,
+}
+
+impl BiquadChannel {
+    fn process(&mut self, c: &BiquadCoefficients, input: f32) -> f32 {
+        // Transposed direct form II
+        let output = c.b0 * input + self.z1;
+        self.z1 = c.b1 * input - c.a1 * output + self.z2;
+        self.z2 = c.b2 * input - c.a2 * output;
+        output
+    }
+}
+
+/// A stereo biquad filter
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Biquad {
+    left: BiquadChannel,
+    right: BiquadChannel,
+}
+
+impl Biquad {
+    /// Filter a single frame
+    pub fn process(&mut self, c: &BiquadCoefficients, input: Voice) -> Voice {
+        Voice::stereo(
+            self.left.process(c, input.left),
+            self.right.process(c, input.right),
+        )
+    }
+}
+
+/// The delay lengths of the Freeverb comb filters at 44.1 kHz
+const COMB_TUNINGS: [f32; 8] = [
+    1116.0, 1188.0, 1277.0, 1356.0, 1422.0, 1491.0, 1557.0, 1617.0,
+];
+/// The delay lengths of the Freeverb allpass filters at 44.1 kHz
+const ALLPASS_TUNINGS: [f32; 4] = [556.0, 441.0, 341.0, 225.0];
+/// The extra delay of the right channel's filters
+const STEREO_SPREAD: f32 = 23.0;
+/// The largest factor that the delay lengths are scaled by
+const MAX_ROOM_SCALE: f32 = 4.0;
+
+/// Map a room size to the factor that the delay lengths are scaled by
+///
+/// A size of 1 keeps the Freeverb tunings. Larger sizes approach `MAX_ROOM_SCALE`
+/// without reaching it, so any size is usable.
+fn room_scale(size: f32) -> f32 {
+    let size = size.max(0.0);
+    (MAX_ROOM_SCALE * size / (size + MAX_ROOM_SCALE - 1.0)).max(0.05)
+}
+
+/// A lowpass-feedback comb filter
+#[derive(Debug, Clone)]
+struct Comb {
+    line: DelayLine<f32>,
+    length: f32,
+    damped: f32,
+}
+
+impl Comb {
+    fn new(length: f32) -> Self {
+        Comb {
+            line: DelayLine::new((length * MAX_ROOM_SCALE).ceil() as usize),
+            length,
+            damped: 0.0,
+        }
+    }
+    fn process(&mut self, input: f32, size: f32, feedback: f32, damping: f32) -> f32 {
+        let output = self.line.read(self.length * size);
+        self.damped = output * (1.0 - damping) + self.damped * damping;
+        self.line.push(input + self.damped * feedback);
+        output
+    }
+}
+
+/// A Schroeder allpass filter
+#[derive(Debug, Clone)]
+struct Allpass {
+    line: DelayLine<f32>,
+    length: f32,
+}
+
+impl Allpass {
+    fn new(length: f32) -> Self {
+        Allpass {
+            line: DelayLine::new((length * MAX_ROOM_SCALE).ceil() as usize),
+            length,
+        }
+    }
+    fn process(&mut self, input: f32, size: f32) -> f32 {
+        let delayed = self.line.read(self.length * size);
+        self.line.push(input + delayed * 0.5);
+        delayed - input
+    }
+}
+
+/// The filters for a single channel of a Freeverb reverb
+#[derive(Debug, Clone)]
+struct FreeverbChannel {
+    combs: Vec<Comb>,
+    allpasses: Vec<Allpass>,
+}
+
+impl FreeverbChannel {
+    fn new(scale: f32, spread: f32) -> Self {
+        FreeverbChannel {
+            combs: COMB_TUNINGS
+                .iter()
+                .map(|len| Comb::new((len + spread) * scale))
+                .collect(),
+            allpasses: ALLPASS_TUNINGS
+                .iter()
+                .map(|len| Allpass::new((len + spread) * scale))
+                .collect(),
+        }
+    }
+    fn process(&mut self, input: f32, size: f32, feedback: f32, damping: f32) -> f32 {
+        let mut output: f32 = self
+            .combs
+            .iter_mut()
+            .map(|comb| comb.process(input, size, feedback, damping))
+            .sum();
+        for allpass in &mut self.allpasses {
+            output = allpass.process(output, size);
+        }
+        output
+    }
+}
+
+/// A Freeverb-style reverb made of parallel comb filters followed by allpass filters
+#[derive(Debug, Clone)]
+pub struct Freeverb {
+    left: FreeverbChannel,
+    right: FreeverbChannel,
+}
+
+impl Freeverb {
+    /// Create a new reverb for a sample rate
+    pub fn new(sample_rate: u32) -> Self {
+        let scale = sample_rate as f32 / 44100.0;
+        Freeverb {
+            left: FreeverbChannel::new(scale, 0.0),
+            right: FreeverbChannel::new(scale, STEREO_SPREAD),
+        }
+    }
+    /// Get the wet output for a frame of input
+    ///
+    /// `size` is any non-negative room size, where 1 is a medium room.
+    /// `decay` and `damping` range from 0 to 1.
+    pub fn process(&mut self, input: Voice, size: f32, decay: f32, damping: f32) -> Voice {
+        let input = (input.left + input.right) * 0.015;
+        let size = room_scale(size);
+        let feedback = 0.7 + 0.28 * decay.max(0.0).min(1.0);
+        let damping = 0.4 * damping.max(0.0).min(1.0);
+        Voice::stereo(
+            self.left.process(input, size, feedback, damping),
+            self.right.process(input, size, feedback, damping),
+        ) * 3.0
+    }
+}
+
+/// A first-order allpass filter for a single channel
+#[derive(Debug, Clone, Copy, Default)]
+pub struct AllpassStage {
+    x1: f32,
+    y1: f32,
+}
+
+impl AllpassStage {
+    /// Get the coefficient that puts the filter's 90 degree phase shift at a frequency
+    pub fn coefficient(freq: f32, sample_rate: u32) -> f32 {
+        let t = (PI * freq.max(1.0) / sample_rate as f32).min(1.5).tan();
+        (t - 1.0) / (t + 1.0)
+    }
+    /// Filter a single value
+    pub fn process(&mut self, coefficient: f32, input: f32) -> f32 {
+        let output = coefficient * input + self.x1 - coefficient * self.y1;
+        self.x1 = input;
+        self.y1 = output;
+        output
+    }
+}
+
+/// Convert an amplitude to decibels
+pub fn amp_to_db(amp: f32) -> f32 {
+    20.0 * amp.abs().max(1e-6).log10()
+}
+
+/// Convert decibels to an amplitude
+pub fn db_to_amp(db: f32) -> f32 {
+    10_f32.powf(db / 20.0)
+}
+
+/// Get the gain change in decibels that a compressor applies to a level
+///
+/// The knee is the width in decibels of the soft transition around the threshold
+pub fn compression_gain(level: f32, threshold: f32, ratio: f32, knee: f32) -> f32 {
+    let over = level - threshold;
+    let slope = 1.0 / ratio.max(1.0) - 1.0;
+    if 2.0 * over <= -knee {
+        0.0
+    } else if 2.0 * over.abs() < knee {
+        slope * (over + knee / 2.0).powi(2) / (2.0 * knee)
+    } else {
+        slope * over
+    }
+}
+
+/// Get the coefficient of a one-pole smoother that settles in the given time
+pub fn smoothing_coefficient(time: f32, sample_rate: u32) -> f32 {
+    if time <= 0.0 {
+        0.0
+    } else {
+        (-1.0 / (time * sample_rate as f32)).exp()
+    }
+}
+
+/// A look-ahead peak limiter
+#[derive(Debug, Clone)]
+pub struct Limiter {
+    /// The maximum output amplitude
+    ceiling: f32,
+    /// The look-ahead time in frames
+    lookahead: usize,
+    release: f32,
+    line: DelayLine<Voice>,
+    /// The frame number and required gain of upcoming peaks, in increasing order of gain
+    peaks: VecDeque<(u64, f32)>,
+    frame: u64,
+    gain: f32,
+}
+
+impl Limiter {
+    /// Create a new limiter
+    ///
+    /// The ceiling is in decibels. The look-ahead and release times are in seconds.
+    pub fn new(sample_rate: u32, ceiling: f32, lookahead: f32, release: f32) -> Self {
+        let lookahead = (lookahead.max(0.0) * sample_rate as f32) as usize;
+        Limiter {
+            ceiling: db_to_amp(ceiling.min(0.0)),
+            lookahead,
+            release: smoothing_coefficient(release, sample_rate),
+            line: DelayLine::new(lookahead + 1),
+            peaks: VecDeque::new(),
+            frame: 0,
+            gain: 1.0,
+        }
+    }
+    /// Limit a frame
+    ///
+    /// The output is delayed by the look-ahead time
+    pub fn process(&mut self, input: Voice) -> Voice {
+        // Track the lowest gain required by any frame in the look-ahead window
+        let peak = input.left.abs().max(input.right.abs());
+        let required = if peak > self.ceiling {
+            self.ceiling / peak
+        } else {
+            1.0
+        };
+        while matches!(self.peaks.back(), Some(&(_, gain)) if gain >= required) {
+            self.peaks.pop_back();
+        }
+        self.peaks.push_back((self.frame, required));
+        let oldest = self.frame.saturating_sub(self.lookahead as u64);
+        while matches!(self.peaks.front(), Some(&(frame, _)) if frame < oldest) {
+            self.peaks.pop_front();
+        }
+        let target = self.peaks.front().map_or(1.0, |&(_, gain)| gain);
+        self.frame += 1;
+        // Ramp the gain down over the look-ahead time and release it afterwards
+        let coef = if target < self.gain {
+            (-4.6 / self.lookahead.max(1) as f32).exp()
+        } else {
+            self.release
+        };
+        self.gain = target + (self.gain - target) * coef;
+        self.line.push(input);
+        let output = self.line.read(self.lookahead as f32 + 1.0) * self.gain;
+        // Clip anything the gain ramp did not catch
+        Voice::stereo(
+            output.left.max(-self.ceiling).min(self.ceiling),
+            output.right.max(-self.ceiling).min(self.ceiling),
+        )
+    }
+}
+
+/// A pitch shifter that crossfades between two taps sweeping through a delay line
+#[derive(Debug, Clone)]
+pub struct PitchShifter {
+    line: DelayLine<Voice>,
+    /// The length of the crossfade window in frames
+    window: f32,
+    /// The position of the first tap in the window, from 0 to 1
+    phase: f32,
+}
+
+impl PitchShifter {
+    /// Create a new pitch shifter with a window length in frames
+    pub fn new(window: usize) -> Self {
+        PitchShifter {
+            line: DelayLine::new(window + 2),
+            window: window as f32,
+            phase: 0.0,
+        }
+    }
+    /// Shift a frame by a frequency ratio
+    pub fn process(&mut self, input: Voice, ratio: f32) -> Voice {
+        self.line.push(input);
+        // The taps move through the line at a speed that changes the pitch by the ratio
+        self.phase = (self.phase + (1.0 - ratio) / self.window).rem_euclid(1.0);
+        let tap = |phase: f32| {
+            let gain = (PI * phase).sin().powi(2);
+            self.line.read(1.0 + phase * self.window) * gain
+        };
+        tap(self.phase) + tap((self.phase + 0.5).fract())
+    }
+}
+
+/// Shape a value with a distortion curve
+pub fn waveshape(curve: &DistortionCurve, x: f32) -> f32 {
+    match curve {
+        DistortionCurve::Hard => x.max(-1.0).min(1.0),
+        DistortionCurve::Tanh => x.tanh(),
+        DistortionCurve::Arctan => x.atan() * 2.0 / PI,
+        DistortionCurve::Foldback => ((x - 1.0).rem_euclid(4.0) - 2.0).abs() - 1.0,
+        DistortionCurve::Tube => {
+            // Biasing the curve makes it asymmetric
+            const BIAS: f32 = 0.3;
+            (x + BIAS).tanh() - BIAS.tanh()
+        }
+        DistortionCurve::Table(points) => match points.len() {
+            0 => x.max(-1.0).min(1.0),
+            1 => points[0],
+            len => {
+                let pos = (x.max(-1.0).min(1.0) + 1.0) / 2.0 * (len - 1) as f32;
+                let i = (pos.floor() as usize).min(len - 2);
+                let frac = pos - i as f32;
+                points[i] * (1.0 - frac) + points[i + 1] * frac
+            }
+        },
+    }
+}
+
+/// Runs a nonlinear function at a multiple of the sample rate to reduce aliasing
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Oversampler {
+    previous: Voice,
+    filters: [Biquad; 2],
+}
+
+impl Oversampler {
+    /// Process a frame with a function at `factor` times the sample rate
+    pub fn process<F>(&mut self, sample_rate: u32, factor: u8, input: Voice, f: F) -> Voice
+    where
+        F: Fn(Voice) -> Voice,
+    {
+        if factor <= 1 {
+            self.previous = input;
+            return f(input);
+        }
+        let factor = u32::from(factor);
+        // Remove the frequencies that the function adds above the original Nyquist frequency
+        let coefficients = BiquadCoefficients::new(
+            BiquadShape::LowPass,
+            sample_rate * factor,
+            sample_rate as f32 * 0.45,
+            0.707,
+            0.0,
+        );
+        let mut output = Voice::SILENT;
+        for i in 1..=factor {
+            let t = i as f32 / factor as f32;
+            let upsampled = self.previous * (1.0 - t) + input * t;
+            output = self.filters.iter_mut().fold(f(upsampled), |acc, filter| {
+                filter.process(&coefficients, acc)
+            });
+        }
+        self.previous = input;
+        output
+    }
+}
@@ -1,6 +1,9 @@
 use std::collections::HashMap;
 
-use crate::{spec::ADSR, ty::Control};
+use crate::{
+    spec::{VoiceMode, VoiceSteal, ADSR},
+    ty::{Control, Float},
+};
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 enum EnvelopeState {
@@ -14,19 +17,54 @@ enum EnvelopeState {
 #[derive(Debug, Clone, Copy)]
 struct NoteEnvelope {
     note: u8,
+    pitch: f32,
     state: EnvelopeState,
     velocity: u8,
     amplitude: f32,
     t: f32,
     sustained: bool,
+    /// The playback phase of the voice, which is reset when the voice is retriggered
+    phase: f64,
+    /// What the phase belongs to, as chosen by the node that advances it
+    source: u64,
 }
 
 #[derive(Debug, Clone, Copy)]
 pub struct EnvelopeFrame {
+    /// The id of the note that started the voice
+    pub id: u64,
     pub note: u8,
+    pub velocity: u8,
+    /// The current pitch, which differs from the note while gliding
+    pub pitch: f32,
     pub amplitude: f32,
     pub pitch_bend: f32,
-    pub t: f32,
+    phase: f64,
+    source: u64,
+}
+
+impl EnvelopeFrame {
+    /// Get the playback phase of the voice
+    ///
+    /// The phase starts over if it was last set for a different source
+    pub fn phase(&self, source: u64) -> f64 {
+        if self.source == source {
+            self.phase
+        } else {
+            0.0
+        }
+    }
+}
+
+/// Settings that determine how an enveloper allocates its voices
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Voicing {
+    /// The maximum number of voices that can play at once
+    pub max_voices: Option<usize>,
+    /// The policy for choosing which voice to replace
+    pub steal: VoiceSteal,
+    /// Whether notes play polyphonically or on a single voice
+    pub mode: VoiceMode,
 }
 
 /// Keeps track of the key states of an input node
@@ -35,26 +73,47 @@ pub struct EnvelopeFrame {
 pub struct Enveloper {
     pitch_bend: f32,
     envelopes: HashMap<u64, NoteEnvelope>,
+    /// The notes currently held in a monophonic mode, in the order they were played
+    held: Vec<(u64, u8, u8)>,
 }
 
 impl Enveloper {
     /// Register some controls
-    pub fn register<I>(&mut self, iter: I, sustain: bool)
+    pub fn register<I>(&mut self, iter: I, sustain: bool, voicing: Voicing)
     where
         I: IntoIterator<Item = Control>,
     {
         for control in iter {
             match control {
+                Control::NoteStart(id, n, v) if voicing.mode != VoiceMode::Poly => {
+                    self.held.push((id, n, v));
+                    self.play_mono(id, n, v, voicing.mode);
+                }
+                Control::NoteEnd(id, _) if voicing.mode != VoiceMode::Poly => {
+                    self.held.retain(|(held_id, ..)| *held_id != id);
+                    if self.envelopes.contains_key(&id) {
+                        // Return to the last note that is still held
+                        if let Some(&(last_id, n, v)) = self.held.last() {
+                            self.play_mono(last_id, n, v, voicing.mode);
+                        } else if !sustain {
+                            self.end_notes(id);
+                        }
+                    }
+                }
                 Control::NoteStart(id, n, v) => {
+                    let amplitude = self.make_room(n, voicing);
                     self.envelopes.insert(
                         id,
                         NoteEnvelope {
                             note: n,
+                            pitch: f32::from(n),
                             state: EnvelopeState::Attack,
                             velocity: v,
-                            amplitude: 0.0,
+                            amplitude,
                             t: 0.0,
                             sustained: false,
+                            phase: 0.0,
+                            source: 0,
                         },
                     );
                 }
@@ -70,15 +129,90 @@ impl Enveloper {
             }
         }
     }
+    /// Play a note on the single voice used by the monophonic modes
+    fn play_mono(&mut self, id: u64, note: u8, velocity: u8, mode: VoiceMode) {
+        let ne = if let Some((_, mut ne)) = self.envelopes.drain().next() {
+            ne.note = note;
+            // Legato notes only retrigger the envelope if the previous note was released
+            if mode == VoiceMode::Mono || ne.state == EnvelopeState::Release {
+                ne.state = EnvelopeState::Attack;
+                ne.velocity = velocity;
+                ne.t = 0.0;
+                ne.phase = 0.0;
+            }
+            ne
+        } else {
+            NoteEnvelope {
+                note,
+                pitch: f32::from(note),
+                state: EnvelopeState::Attack,
+                velocity,
+                amplitude: 0.0,
+                t: 0.0,
+                sustained: false,
+                phase: 0.0,
+                source: 0,
+            }
+        };
+        self.envelopes.insert(id, ne);
+    }
+    /// Free up a voice for a new note if necessary
+    ///
+    /// Returns the amplitude at which the new note should start
+    fn make_room(&mut self, note: u8, voicing: Voicing) -> f32 {
+        // Retrigger a voice playing the same note
+        if voicing.steal == VoiceSteal::SameNote {
+            let same = self
+                .envelopes
+                .iter()
+                .find(|(_, ne)| ne.note == note)
+                .map(|(id, _)| *id);
+            if let Some(ne) = same.and_then(|id| self.envelopes.remove(&id)) {
+                return ne.amplitude;
+            }
+        }
+        // Steal voices until there is room
+        if let Some(max_voices) = voicing.max_voices {
+            while self.envelopes.len() >= max_voices.max(1) {
+                let stolen = self
+                    .envelopes
+                    .iter()
+                    // Prefer voices that are already being released
+                    .min_by(|(_, a), (_, b)| {
+                        let a_released = a.state == EnvelopeState::Release;
+                        let b_released = b.state == EnvelopeState::Release;
+                        b_released
+                            .cmp(&a_released)
+                            .then_with(|| match voicing.steal {
+                                VoiceSteal::Quietest => Float(a.amplitude).cmp(&Float(b.amplitude)),
+                                VoiceSteal::Oldest | VoiceSteal::SameNote => {
+                                    Float(b.t).cmp(&Float(a.t))
+                                }
+                            })
+                    })
+                    .map(|(id, _)| *id);
+                if let Some(id) = stolen {
+                    self.envelopes.remove(&id);
+                } else {
+                    break;
+                }
+            }
+        }
+        0.0
+    }
     /// Get an iterator of frequency-amplitude pairs that are currently playing
     pub fn envelopes(&self) -> impl Iterator<Item = EnvelopeFrame> + '_ {
-        self.envelopes.iter().filter_map(move |(_, ne)| {
+        self.envelopes.iter().filter_map(move |(&id, ne)| {
             if ne.amplitude > 0.0 {
                 Some(EnvelopeFrame {
+                    id,
                     note: ne.note,
+                    velocity: ne.velocity,
+                    pitch: ne.pitch,
                     pitch_bend: self.pitch_bend,
                     amplitude: ne.amplitude,
-                    t: ne.t,
+                    phase: ne.phase,
+                    source: ne.source,
                 })
             } else {
                 None
@@ -86,9 +220,13 @@ impl Enveloper {
         })
     }
     /// Progress the enveloper to the next frame
-    pub fn progress(&mut self, sample_rate: u32, adsr: ADSR<f32>, sustain: bool) {
+    ///
+    /// `glide` is the time in seconds it takes a voice to approach a new note's pitch
+    pub fn progress(&mut self, sample_rate: u32, adsr: ADSR<f32>, sustain: bool, glide: f32) {
         const MIN_VAL: f32 = 0.001;
+        let glide_factor = (1.0 / (glide.max(0.0) * sample_rate as f32)).min(1.0);
         for ne in self.envelopes.values_mut() {
+            ne.pitch += (f32::from(ne.note) - ne.pitch) * glide_factor;
             let velocity = f32::from(ne.velocity) / 127.0;
             match ne.state {
                 EnvelopeState::Attack => {
@@ -135,7 +273,15 @@ impl Enveloper {
         self.envelopes
             .retain(|_, ne| !matches!(ne.state, EnvelopeState::Done));
     }
+    /// Set the playback phase of a voice and what it belongs to
+    pub fn set_phase(&mut self, id: u64, source: u64, phase: f64) {
+        if let Some(ne) = self.envelopes.get_mut(&id) {
+            ne.phase = phase;
+            ne.source = source;
+        }
+    }
     pub fn end_notes(&mut self, id: u64) {
+        self.held.retain(|(held_id, ..)| *held_id != id);
         if let Some(ne) = self.envelopes.get_mut(&id) {
             ne.state = EnvelopeState::Release;
         }
@@ -1,4 +1,4 @@
-use std::io;
+use std::{io, path::PathBuf};
 
 use structopt::clap;
 use thiserror::Error;
@@ -59,6 +59,12 @@ pub enum RyvmError {
     /// No default input node
     #[error("Tried to create an input-pass, but there is no audio input specified")]
     NoAudioInputForPass,
+    /// An error parsing a Scala tuning file
+    #[error("Error parsing tuning file {0:?}: {1}")]
+    Tuning(PathBuf, String),
+    /// An error parsing an instrument file
+    #[error("Error parsing instrument file {0:?}: {1}")]
+    Instrument(PathBuf, String),
 }
 
 /// The Ryvm result type
@@ -24,6 +24,10 @@ pub fn loops_dir() -> io::Result<PathBuf> {
     ensure_dir_exists("Loops dir", ryvm_dir()?.join("loops"))
 }
 
+pub fn tunings_dir() -> io::Result<PathBuf> {
+    ensure_dir_exists("Tunings dir", ryvm_dir()?.join("tunings"))
+}
+
 pub fn startup_path() -> io::Result<PathBuf> {
     let path = specs_dir()?.join("startup.toml");
     if !path.exists() {
@@ -2,6 +2,8 @@ mod utility;
 
 mod app;
 mod channel;
+mod clock;
+mod dsp;
 mod envelope;
 mod error;
 mod gamepad;
@@ -12,16 +14,17 @@ mod midi;
 mod node;
 mod onfly;
 mod sample;
+mod sf2;
+mod sfz;
 mod spec;
 mod state;
-mod track;
+mod tuning;
 
 mod ty {
     pub use crate::{
         channel::Voice,
         midi::{Control, Port},
         spec::Name,
-        track::Letter,
         utility::Float,
         Frame,
     };
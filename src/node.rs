@@ -1,14 +1,29 @@
-use std::{collections::VecDeque, f32::consts::PI, iter::once, path::PathBuf};
+use std::{
+    collections::{HashMap, HashSet, VecDeque},
+    f32::consts::PI,
+    iter::once,
+    path::PathBuf,
+};
 
 use rand::random;
 
 use crate::{
     channel::{Channel, FrameCache},
-    envelope::Enveloper,
-    sample::ActiveSampling,
-    spec::{DynamicValue, FilterType, SampleDef, WaveForm, ADSR},
+    clock::{NoteGates, StepClock},
+    dsp::{
+        self, AllpassStage, Biquad, BiquadCoefficients, BiquadShape, DelayLine, Freeverb,
+        Oversampler, PitchShifter,
+    },
+    envelope::{Enveloper, Voicing},
+    sample::{ActiveSampling, BeatPattern},
+    spec::{
+        ArpMode, ChordDef, DistortionCurve, DynamicValue, EqBand, EqShape, FilterType,
+        Interpolation, MixInput, ModulationMode, PadDef, PitchShiftMode, SampleDef, Scale,
+        WaveForm, ZoneDef, ADSR,
+    },
     state::State,
-    ty::{Control, Float, Frame, Letter, Name, Voice},
+    tuning::Tuning,
+    ty::{Control, Float, Frame, Name, Voice},
     utility::{CloneCell, CloneLock},
 };
 
@@ -24,11 +39,41 @@ pub enum Node {
     /// A volume and pan balancer
     Balance(Balance),
     /// A reverb simulator
-    Reverb(Reverb),
+    Reverb(Box<Reverb>),
     /// A pitch-changing sampler
     Sampler(Box<Sampler>),
     /// A channel-bound input interface
     InputPass(InputPass),
+    /// A plucked string synthesizer
+    Pluck(Box<Pluck>),
+    /// A multi-sample instrument
+    Instrument(Box<Instrument>),
+    /// A step sequencer
+    Sequencer(Box<Sequencer>),
+    /// An arpeggiator
+    Arp(Box<Arp>),
+    /// A scale lock and chord generator
+    NoteProcessor(Box<NoteProcessor>),
+    /// An echoing delay
+    Delay(Box<Delay>),
+    /// A chorus, flanger, or phaser
+    Modulation(Box<Modulation>),
+    /// A compressor
+    Compressor(Box<Compressor>),
+    /// A parametric equalizer
+    Equalizer(Equalizer),
+    /// A mixer that sums several inputs
+    Mixer(Mixer),
+    /// Sends its input to a bus
+    BusSend(BusSend),
+    /// Outputs everything sent to its channel
+    BusReturn(BusReturn),
+    /// A noise gate
+    Gate(Gate),
+    /// Outputs the amplitude envelope of its input
+    Follower(Follower),
+    /// A pitch shifter and harmonizer
+    PitchShift(Box<PitchShift>),
 }
 
 /// A wave synthesizer
@@ -45,6 +90,12 @@ pub struct Wave {
     pub adsr: ADSR<DynamicValue>,
     /// The sustain control
     pub sustain_pedal: DynamicValue,
+    /// The voice allocation settings
+    pub voicing: Voicing,
+    /// The glide time
+    pub glide: DynamicValue,
+    /// The tuning
+    pub tuning: Tuning,
     enveloper: CloneLock<Enveloper>,
 }
 
@@ -60,15 +111,25 @@ impl Wave {
             enveloper: CloneLock::new(Enveloper::default()),
             waves: CloneLock::new(vec![0; 10]),
             sustain_pedal: DynamicValue::Static(0.0),
+            voicing: Voicing::default(),
+            glide: DynamicValue::Static(0.0),
+            tuning: Tuning::default(),
         }
     }
 }
 
+/// The time in seconds it takes a choked drum pad to fade out
+const CHOKE_FADE_TIME: f32 = 0.005;
+
 /// A drum machine
 #[derive(Debug, Clone, Default)]
 pub struct DrumMachine {
     pub samples: Vec<PathBuf>,
     pub samplings: CloneLock<Vec<ActiveSampling>>,
+    /// The method used to read between sample frames
+    pub interpolation: Interpolation,
+    /// The settings for each pad
+    pub pads: Vec<PadDef>,
 }
 
 impl DrumMachine {
@@ -78,8 +139,14 @@ impl DrumMachine {
         DrumMachine {
             samples: Vec::new(),
             samplings: CloneLock::new(Vec::new()),
+            interpolation: Interpolation::default(),
+            pads: Vec::new(),
         }
     }
+    /// Get the settings for a pad
+    pub fn pad(&self, index: usize) -> PadDef {
+        self.pads.get(index).cloned().unwrap_or_default()
+    }
 }
 
 #[derive(Debug, Clone)]
@@ -87,48 +154,121 @@ enum FilterState {
     LowPass(CloneCell<Voice>),
     Comb(CloneLock<VecDeque<Voice>>),
     Crush {
-        counter: CloneCell<usize>,
+        /// The progress until the next sample is taken
+        phase: CloneCell<f32>,
         voice: CloneCell<Voice>,
+        anti_alias: CloneCell<Biquad>,
+    },
+    Distortion {
+        oversampler: CloneCell<Oversampler>,
+        tone: CloneCell<Biquad>,
     },
-    Distortion,
+    Biquad(BiquadShape, CloneCell<Biquad>),
 }
 
 impl From<FilterType> for FilterState {
     fn from(ty: FilterType) -> Self {
+        let biquad = |shape| FilterState::Biquad(shape, CloneCell::new(Biquad::default()));
         match ty {
             FilterType::LowPass => FilterState::LowPass(CloneCell::new(Voice::SILENT)),
             FilterType::Comb => FilterState::Comb(CloneLock::new(VecDeque::new())),
             FilterType::Crush => FilterState::Crush {
-                counter: CloneCell::new(0),
+                phase: CloneCell::new(1.0),
                 voice: CloneCell::new(Voice::SILENT),
+                anti_alias: CloneCell::new(Biquad::default()),
+            },
+            FilterType::Distortion => FilterState::Distortion {
+                oversampler: CloneCell::new(Oversampler::default()),
+                tone: CloneCell::new(Biquad::default()),
+            },
+            FilterType::ResonantLowPass => biquad(BiquadShape::LowPass),
+            FilterType::HighPass => biquad(BiquadShape::HighPass),
+            FilterType::BandPass => biquad(BiquadShape::BandPass),
+            FilterType::Notch => biquad(BiquadShape::Notch),
+            FilterType::Peak => biquad(BiquadShape::Peak),
+            FilterType::LowShelf => biquad(BiquadShape::LowShelf),
+            FilterType::HighShelf => biquad(BiquadShape::HighShelf),
+        }
+    }
+}
+
+impl FilterState {
+    fn ty(&self) -> FilterType {
+        match self {
+            FilterState::LowPass(_) => FilterType::LowPass,
+            FilterState::Comb(_) => FilterType::Comb,
+            FilterState::Crush { .. } => FilterType::Crush,
+            FilterState::Distortion { .. } => FilterType::Distortion,
+            FilterState::Biquad(shape, _) => match shape {
+                BiquadShape::LowPass => FilterType::ResonantLowPass,
+                BiquadShape::HighPass => FilterType::HighPass,
+                BiquadShape::BandPass => FilterType::BandPass,
+                BiquadShape::Notch => FilterType::Notch,
+                BiquadShape::Peak => FilterType::Peak,
+                BiquadShape::LowShelf => FilterType::LowShelf,
+                BiquadShape::HighShelf => FilterType::HighShelf,
             },
-            FilterType::Distortion => FilterState::Distortion,
         }
     }
 }
 
 impl PartialEq<FilterType> for FilterState {
     fn eq(&self, ty: &FilterType) -> bool {
-        match (self, ty) {
-            (FilterState::LowPass(_), FilterType::LowPass)
-            | (FilterState::Comb(_), FilterType::Comb) => true,
-            _ => false,
-        }
+        self.ty() == *ty
     }
 }
 
-/// A low-pass filter
+/// The lowest cutoff frequency of the resonant filters
+const MIN_CUTOFF: f32 = 20.0;
+/// The ratio of the highest cutoff frequency of the resonant filters to the lowest
+const CUTOFF_RANGE: f32 = 1000.0;
+
+/// Map a value from 0 to 1 to a cutoff frequency
+fn cutoff(value: f32) -> f32 {
+    MIN_CUTOFF * CUTOFF_RANGE.powf(value.max(0.0).min(1.0))
+}
+
+/// Get the left and right gains for a pan from -1 (right) to 1 (left)
+fn pan_gains(pan: f32) -> Voice {
+    Voice::stereo((1.0 + pan).min(1.0).max(0.0), (1.0 - pan).min(1.0).max(0.0))
+}
+
+/// A filter
 #[derive(Debug, Clone)]
 pub struct Filter {
     /// The name of the input node
     pub input: Name,
     /// The value used to determine filter strength
     pub value: DynamicValue,
+    /// The resonance of the resonant filter types
+    pub resonance: DynamicValue,
+    /// How much the envelope adds to the resonance
+    pub resonance_env: DynamicValue,
+    /// The gain in decibels of the peak and shelf filter types
+    pub gain: DynamicValue,
+    /// The bit depth of the crush filter type
+    pub bits: DynamicValue,
+    /// The relative sample rate of the crush filter type
+    pub rate: Option<DynamicValue>,
+    /// Whether the crush filter type is anti-aliased
+    pub anti_alias: bool,
+    /// The curve of the distortion filter type
+    pub curve: DistortionCurve,
+    /// The drive of the distortion filter type
+    pub drive: DynamicValue,
+    /// The tone of the distortion filter type
+    pub tone: DynamicValue,
+    /// The oversampling factor of the distortion filter type
+    pub oversample: u8,
+    /// The balance between the dry and wet signals of the crush and distortion filter types
+    pub mix: DynamicValue,
     state: FilterState,
     /// The attack-decay-sustain-release envelope
     pub adsr: Option<ADSR<DynamicValue>>,
     /// The sustain control
     pub sustain_pedal: DynamicValue,
+    /// The voice allocation settings
+    pub voicing: Voicing,
     enveloper: CloneLock<Enveloper>,
 }
 
@@ -139,10 +279,22 @@ impl Filter {
         Filter {
             input,
             value,
+            resonance: DynamicValue::Static(0.707),
+            resonance_env: DynamicValue::Static(0.0),
+            gain: DynamicValue::Static(0.0),
+            bits: DynamicValue::Static(24.0),
+            rate: None,
+            anti_alias: false,
+            curve: DistortionCurve::default(),
+            drive: DynamicValue::Static(1.0),
+            tone: DynamicValue::Static(1.0),
+            oversample: 1,
+            mix: DynamicValue::Static(1.0),
             state: ty.into(),
             adsr: None,
             enveloper: CloneLock::new(Enveloper::default()),
             sustain_pedal: DynamicValue::Static(0.0),
+            voicing: Voicing::default(),
         }
     }
     pub fn set_type(&mut self, ty: FilterType) {
@@ -175,26 +327,402 @@ impl Balance {
     }
 }
 
-const SPEED_OF_SOUND: f32 = 340.27;
+/// A mixer that sums several inputs
+#[derive(Debug, Clone, Default)]
+pub struct Mixer {
+    /// The inputs and their gains and pans
+    pub inputs: Vec<MixInput>,
+}
+
+impl Mixer {
+    /// Create a new mixer with no inputs
+    #[must_use]
+    pub fn new() -> Self {
+        Mixer::default()
+    }
+}
+
+/// The longest supported reverb pre-delay in seconds
+const MAX_PRE_DELAY: f32 = 1.0;
+
+/// The delay lines of a reverb
+#[derive(Debug, Clone)]
+struct ReverbState {
+    freeverb: Freeverb,
+    pre_delay: DelayLine<Voice>,
+}
+
+impl ReverbState {
+    fn new(sample_rate: u32) -> Self {
+        ReverbState {
+            freeverb: Freeverb::new(sample_rate),
+            pre_delay: DelayLine::new((MAX_PRE_DELAY * sample_rate as f32) as usize + 1),
+        }
+    }
+}
 
 /// A reverb simulator
 #[derive(Debug, Clone)]
 pub struct Reverb {
     pub input: Name,
+    /// The simulated room size
     pub size: DynamicValue,
-    pub energy_mul: DynamicValue,
-    frames: CloneLock<VecDeque<Voice>>,
+    /// How long the reverb rings out
+    pub decay: DynamicValue,
+    /// How much high frequencies are absorbed
+    pub damping: DynamicValue,
+    /// The time in seconds before the reverb starts
+    pub pre_delay: DynamicValue,
+    /// The balance between the dry and wet signals
+    pub mix: DynamicValue,
+    lines: CloneLock<ReverbState>,
 }
 
 impl Reverb {
     /// Create a new reverb
     #[must_use]
-    pub fn new(input: Name) -> Self {
+    pub fn new(input: Name, sample_rate: u32) -> Self {
         Reverb {
             input,
             size: DynamicValue::Static(1.0),
-            energy_mul: DynamicValue::Static(0.5),
-            frames: CloneLock::new(VecDeque::new()),
+            decay: DynamicValue::Static(0.5),
+            damping: DynamicValue::Static(0.5),
+            pre_delay: DynamicValue::Static(0.0),
+            mix: DynamicValue::Static(0.5),
+            lines: CloneLock::new(ReverbState::new(sample_rate)),
+        }
+    }
+}
+
+/// The longest supported delay time in seconds
+const MAX_DELAY_TIME: f32 = 10.0;
+
+/// The delay line and feedback filter of a delay
+#[derive(Debug, Clone)]
+struct DelayState {
+    line: DelayLine<Voice>,
+    filter: Biquad,
+}
+
+impl DelayState {
+    fn new(sample_rate: u32) -> Self {
+        DelayState {
+            line: DelayLine::new((MAX_DELAY_TIME * sample_rate as f32) as usize),
+            filter: Biquad::default(),
+        }
+    }
+}
+
+/// An echoing delay
+#[derive(Debug, Clone)]
+pub struct Delay {
+    pub input: Name,
+    /// The delay time in seconds
+    pub time: DynamicValue,
+    /// The number of delays in one period of the loop master
+    pub division: Option<f32>,
+    /// How much of each echo is fed back into the delay
+    pub feedback: DynamicValue,
+    /// The cutoff of the low-pass filter in the feedback path
+    pub tone: DynamicValue,
+    /// Whether echoes alternate between the left and right channels
+    pub ping_pong: bool,
+    /// The balance between the dry and wet signals
+    pub mix: DynamicValue,
+    lines: CloneLock<DelayState>,
+}
+
+impl Delay {
+    /// Create a new delay
+    #[must_use]
+    pub fn new(input: Name, sample_rate: u32) -> Self {
+        Delay {
+            input,
+            time: DynamicValue::Static(0.25),
+            division: None,
+            feedback: DynamicValue::Static(0.4),
+            tone: DynamicValue::Static(1.0),
+            ping_pong: false,
+            mix: DynamicValue::Static(0.5),
+            lines: CloneLock::new(DelayState::new(sample_rate)),
+        }
+    }
+}
+
+/// A compressor
+#[derive(Debug, Clone)]
+pub struct Compressor {
+    pub input: Name,
+    /// The node whose level drives the gain reduction
+    pub sidechain: Option<Name>,
+    /// The threshold in decibels
+    pub threshold: DynamicValue,
+    /// The compression ratio
+    pub ratio: DynamicValue,
+    /// The attack time in seconds
+    pub attack: DynamicValue,
+    /// The release time in seconds
+    pub release: DynamicValue,
+    /// The knee width in decibels
+    pub knee: DynamicValue,
+    /// The makeup gain in decibels
+    pub makeup: DynamicValue,
+    /// The current gain reduction in decibels
+    reduction: CloneCell<f32>,
+}
+
+impl Compressor {
+    /// Create a new compressor
+    #[must_use]
+    pub fn new(input: Name) -> Self {
+        Compressor {
+            input,
+            sidechain: None,
+            threshold: DynamicValue::Static(-18.0),
+            ratio: DynamicValue::Static(4.0),
+            attack: DynamicValue::Static(0.01),
+            release: DynamicValue::Static(0.1),
+            knee: DynamicValue::Static(6.0),
+            makeup: DynamicValue::Static(0.0),
+            reduction: CloneCell::new(0.0),
+        }
+    }
+}
+
+/// The length of a pitch shifter's crossfade window in seconds
+const PITCH_SHIFT_WINDOW: f32 = 0.05;
+/// The time in seconds it takes for a harmony voice to fade in or out
+const HARMONY_FADE_TIME: f32 = 0.01;
+
+/// A voice of a harmonizer
+#[derive(Debug, Clone)]
+struct Harmony {
+    note: u8,
+    velocity: f32,
+    /// The current gain, which fades in and out
+    gain: f32,
+    released: bool,
+    shifter: PitchShifter,
+}
+
+/// The shifters of a pitch shifter
+#[derive(Debug, Clone)]
+struct PitchShiftState {
+    shifter: PitchShifter,
+    harmonies: HashMap<u64, Harmony>,
+}
+
+impl PitchShiftState {
+    fn new(sample_rate: u32) -> Self {
+        PitchShiftState {
+            shifter: PitchShifter::new(Self::window(sample_rate)),
+            harmonies: HashMap::new(),
+        }
+    }
+    fn window(sample_rate: u32) -> usize {
+        (PITCH_SHIFT_WINDOW * sample_rate as f32) as usize
+    }
+}
+
+/// A pitch shifter and harmonizer
+#[derive(Debug, Clone)]
+pub struct PitchShift {
+    pub input: Name,
+    /// Whether to shift by a fixed interval or harmonize with held notes
+    pub mode: PitchShiftMode,
+    /// The shift in semitones
+    pub semitones: DynamicValue,
+    /// The fine shift in cents
+    pub cents: DynamicValue,
+    /// The note that is considered the pitch of the input when harmonizing
+    pub root: u8,
+    /// The balance between the dry and wet signals
+    pub mix: DynamicValue,
+    shifters: CloneLock<PitchShiftState>,
+}
+
+impl PitchShift {
+    /// Create a new pitch shifter
+    #[must_use]
+    pub fn new(input: Name, sample_rate: u32) -> Self {
+        PitchShift {
+            input,
+            mode: PitchShiftMode::default(),
+            semitones: DynamicValue::Static(0.0),
+            cents: DynamicValue::Static(0.0),
+            root: 60,
+            mix: DynamicValue::Static(0.5),
+            shifters: CloneLock::new(PitchShiftState::new(sample_rate)),
+        }
+    }
+}
+
+/// The state of a noise gate
+#[derive(Debug, Clone, Copy, Default)]
+struct GateState {
+    gain: f32,
+    /// The remaining hold time in frames
+    hold: u32,
+}
+
+/// A noise gate
+#[derive(Debug, Clone)]
+pub struct Gate {
+    pub input: Name,
+    /// The threshold in decibels
+    pub threshold: DynamicValue,
+    /// The attack time in seconds
+    pub attack: DynamicValue,
+    /// The hold time in seconds
+    pub hold: DynamicValue,
+    /// The release time in seconds
+    pub release: DynamicValue,
+    state: CloneCell<GateState>,
+}
+
+impl Gate {
+    /// Create a new noise gate
+    #[must_use]
+    pub fn new(input: Name) -> Self {
+        Gate {
+            input,
+            threshold: DynamicValue::Static(-40.0),
+            attack: DynamicValue::Static(0.001),
+            hold: DynamicValue::Static(0.05),
+            release: DynamicValue::Static(0.1),
+            state: CloneCell::new(GateState::default()),
+        }
+    }
+}
+
+/// Outputs the amplitude envelope of its input
+#[derive(Debug, Clone)]
+pub struct Follower {
+    pub input: Name,
+    /// The attack time in seconds
+    pub attack: DynamicValue,
+    /// The release time in seconds
+    pub release: DynamicValue,
+    /// The gain applied to the envelope
+    pub gain: DynamicValue,
+    envelope: CloneCell<f32>,
+}
+
+impl Follower {
+    /// Create a new envelope follower
+    #[must_use]
+    pub fn new(input: Name) -> Self {
+        Follower {
+            input,
+            attack: DynamicValue::Static(0.01),
+            release: DynamicValue::Static(0.2),
+            gain: DynamicValue::Static(1.0),
+            envelope: CloneCell::new(0.0),
+        }
+    }
+}
+
+/// A parametric equalizer
+#[derive(Debug, Clone)]
+pub struct Equalizer {
+    pub input: Name,
+    bands: Vec<EqBand>,
+    filters: CloneLock<Vec<Biquad>>,
+}
+
+impl Equalizer {
+    /// Create a new equalizer with no bands
+    #[must_use]
+    pub fn new(input: Name) -> Self {
+        Equalizer {
+            input,
+            bands: Vec::new(),
+            filters: CloneLock::new(Vec::new()),
+        }
+    }
+    /// Set the bands
+    ///
+    /// The state of bands whose shape did not change is kept
+    pub fn set_bands(&mut self, bands: Vec<EqBand>) {
+        let mut filters = self.filters.lock();
+        filters.resize_with(bands.len(), Biquad::default);
+        for (i, band) in bands.iter().enumerate() {
+            if self.bands.get(i).map(|old| old.shape) != Some(band.shape) {
+                filters[i] = Biquad::default();
+            }
+        }
+        drop(filters);
+        self.bands = bands;
+    }
+}
+
+fn eq_shape(shape: EqShape) -> BiquadShape {
+    match shape {
+        EqShape::Peak => BiquadShape::Peak,
+        EqShape::LowShelf => BiquadShape::LowShelf,
+        EqShape::HighShelf => BiquadShape::HighShelf,
+        EqShape::LowPass => BiquadShape::LowPass,
+        EqShape::HighPass => BiquadShape::HighPass,
+        EqShape::BandPass => BiquadShape::BandPass,
+        EqShape::Notch => BiquadShape::Notch,
+    }
+}
+
+/// The number of allpass stages in a phaser
+const PHASER_STAGES: usize = 6;
+/// The longest delay used by a chorus or flanger in seconds
+const MAX_MODULATION_DELAY: f32 = 0.05;
+
+/// The delay line, allpass filters, and LFO of a modulation effect
+#[derive(Debug, Clone)]
+struct ModulationState {
+    line: DelayLine<Voice>,
+    stages: [(AllpassStage, AllpassStage); PHASER_STAGES],
+    /// The phase of the LFO from 0 to 1
+    phase: f32,
+    last_output: Voice,
+}
+
+impl ModulationState {
+    fn new(sample_rate: u32) -> Self {
+        ModulationState {
+            line: DelayLine::new((MAX_MODULATION_DELAY * sample_rate as f32) as usize + 2),
+            stages: Default::default(),
+            phase: 0.0,
+            last_output: Voice::SILENT,
+        }
+    }
+}
+
+/// A chorus, flanger, or phaser
+#[derive(Debug, Clone)]
+pub struct Modulation {
+    pub input: Name,
+    /// The type of modulation
+    pub mode: ModulationMode,
+    /// The rate of the modulation in Hz
+    pub rate: DynamicValue,
+    /// The depth of the modulation
+    pub depth: DynamicValue,
+    /// How much of the output is fed back into the effect
+    pub feedback: DynamicValue,
+    /// The balance between the dry and wet signals
+    pub mix: DynamicValue,
+    lines: CloneLock<ModulationState>,
+}
+
+impl Modulation {
+    /// Create a new modulation effect
+    #[must_use]
+    pub fn new(input: Name, sample_rate: u32) -> Self {
+        Modulation {
+            input,
+            mode: ModulationMode::default(),
+            rate: DynamicValue::Static(0.5),
+            depth: DynamicValue::Static(0.5),
+            feedback: DynamicValue::Static(0.0),
+            mix: DynamicValue::Static(0.5),
+            lines: CloneLock::new(ModulationState::new(sample_rate)),
         }
     }
 }
@@ -203,10 +731,18 @@ impl Reverb {
 #[derive(Debug, Clone)]
 pub struct Sampler {
     pub def: SampleDef,
+    /// The method used to read between sample frames
+    pub interpolation: Interpolation,
     /// The attack-decay-sustain-release envelope
     pub adsr: ADSR<DynamicValue>,
     /// The sustain control
     pub sustain_pedal: DynamicValue,
+    /// The voice allocation settings
+    pub voicing: Voicing,
+    /// The glide time
+    pub glide: DynamicValue,
+    /// The tuning
+    pub tuning: Tuning,
     enveloper: CloneLock<Enveloper>,
 }
 
@@ -216,9 +752,124 @@ impl Sampler {
     pub fn new(def: SampleDef) -> Self {
         Sampler {
             def,
+            interpolation: Interpolation::default(),
             adsr: ADSR::default().map(|f| DynamicValue::Static(*f)),
             enveloper: CloneLock::new(Enveloper::default()),
             sustain_pedal: DynamicValue::Static(0.0),
+            voicing: Voicing::default(),
+            glide: DynamicValue::Static(0.0),
+            tuning: Tuning::default(),
+        }
+    }
+}
+
+/// A plucked string synthesizer
+#[derive(Debug, Clone)]
+pub struct Pluck {
+    /// The octave
+    pub octave: i8,
+    /// The brightness of the string
+    pub brightness: DynamicValue,
+    /// The time it takes the string to decay by 60 dB
+    pub decay: DynamicValue,
+    /// The +- range for pitch bending
+    pub pitch_bend_range: DynamicValue,
+    /// The attack-decay-sustain-release envelope
+    pub adsr: ADSR<DynamicValue>,
+    /// The sustain control
+    pub sustain_pedal: DynamicValue,
+    /// The voice allocation settings
+    pub voicing: Voicing,
+    /// The tuning
+    pub tuning: Tuning,
+    strings: CloneLock<HashMap<u64, PluckString>>,
+    enveloper: CloneLock<Enveloper>,
+}
+
+impl Pluck {
+    /// Create a new pluck
+    #[must_use]
+    pub fn new() -> Self {
+        Pluck {
+            octave: 0,
+            brightness: DynamicValue::Static(0.5),
+            decay: DynamicValue::Static(2.0),
+            pitch_bend_range: DynamicValue::Static(12.0),
+            adsr: ADSR::default().map(|f| DynamicValue::Static(*f)),
+            sustain_pedal: DynamicValue::Static(0.0),
+            voicing: Voicing::default(),
+            tuning: Tuning::default(),
+            strings: CloneLock::new(HashMap::new()),
+            enveloper: CloneLock::new(Enveloper::default()),
+        }
+    }
+}
+
+/// The state of a single Karplus-Strong string
+#[derive(Debug, Clone)]
+struct PluckString {
+    line: DelayLine<f32>,
+    damped: f32,
+}
+
+impl PluckString {
+    /// Create a new string excited by a burst of noise
+    fn new(period: f32, brightness: f32) -> Self {
+        // Leave room for bending down
+        let mut line = DelayLine::new((period * 4.0) as usize + 2);
+        let mut noise = 0.0;
+        for _ in 0..line.capacity() {
+            noise += (random::<f32>() * 2.0 - 1.0 - noise) * (0.1 + 0.9 * brightness);
+            line.push(noise);
+        }
+        PluckString { line, damped: 0.0 }
+    }
+    /// Get the next sample of the string
+    ///
+    /// `feedback` is the gain applied each time the wave travels the string
+    fn next(&mut self, period: f32, brightness: f32, feedback: f32) -> f32 {
+        // The damping filter delays the wave by about (1 - a) / a frames
+        let a = 0.5 + 0.5 * brightness;
+        let output = self.line.read(period - (1.0 - a) / a);
+        self.damped += (output - self.damped) * a;
+        self.line.push(self.damped * feedback);
+        output
+    }
+}
+
+/// A multi-sample instrument
+#[derive(Debug, Clone)]
+pub struct Instrument {
+    /// The zones
+    pub zones: Vec<ZoneDef>,
+    /// The method used to read between sample frames
+    pub interpolation: Interpolation,
+    /// The attack-decay-sustain-release envelope
+    pub adsr: ADSR<DynamicValue>,
+    /// The sustain control
+    pub sustain_pedal: DynamicValue,
+    /// The voice allocation settings
+    pub voicing: Voicing,
+    /// The glide time
+    pub glide: DynamicValue,
+    /// The tuning
+    pub tuning: Tuning,
+    enveloper: CloneLock<Enveloper>,
+}
+
+impl Instrument {
+    /// Create a new instrument
+    #[must_use]
+    pub fn new() -> Self {
+        Instrument {
+            zones: Vec::new(),
+            interpolation: Interpolation::default(),
+            adsr: ADSR::default().map(|f| DynamicValue::Static(*f)),
+            sustain_pedal: DynamicValue::Static(0.0),
+            voicing: Voicing::default(),
+            glide: DynamicValue::Static(0.0),
+            tuning: Tuning::default(),
+            enveloper: CloneLock::new(Enveloper::default()),
         }
     }
 }
@@ -237,6 +888,372 @@ impl InputPass {
     }
 }
 
+/// Sends its input to a bus
+#[derive(Debug, Clone)]
+pub struct BusSend {
+    pub input: Name,
+    /// The channel of the bus
+    pub bus: u8,
+    /// The gain of the sent signal
+    pub level: DynamicValue,
+}
+
+impl BusSend {
+    /// Create a new bus send
+    #[must_use]
+    pub fn new(input: Name, bus: u8) -> Self {
+        BusSend {
+            input,
+            bus,
+            level: DynamicValue::Static(1.0),
+        }
+    }
+}
+
+/// Outputs everything sent to its channel
+#[derive(Debug, Clone, Copy)]
+pub struct BusReturn;
+
+/// What a sequencer track plays
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StepTarget {
+    /// A drum pad
+    Pad(u8),
+    /// A note
+    Note(u8),
+}
+
+/// A single track of a step sequencer
+#[derive(Debug, Clone)]
+pub struct StepTrack {
+    /// What the track plays
+    pub target: StepTarget,
+    /// The pattern of steps
+    pub pattern: BeatPattern,
+    /// The velocity of each hit
+    pub velocity: u8,
+}
+
+/// The playing state of a sequencer
+#[derive(Debug, Clone, Default)]
+struct SequencerState {
+    clock: StepClock,
+    gates: NoteGates,
+}
+
+/// A step sequencer
+#[derive(Debug, Clone)]
+pub struct Sequencer {
+    /// The tracks
+    pub tracks: Vec<StepTrack>,
+    /// The length of a step in seconds
+    pub step: f32,
+    /// The number of steps in one period of the loop master
+    pub division: Option<u16>,
+    /// How far every second step is delayed
+    pub swing: DynamicValue,
+    /// The fraction of a step that notes are held for
+    pub gate: DynamicValue,
+    playing: CloneLock<SequencerState>,
+}
+
+impl Sequencer {
+    /// Create a new sequencer
+    #[must_use]
+    pub fn new() -> Self {
+        Sequencer {
+            tracks: Vec::new(),
+            step: 0.125,
+            division: None,
+            swing: DynamicValue::Static(0.0),
+            gate: DynamicValue::Static(0.5),
+            playing: CloneLock::new(SequencerState::default()),
+        }
+    }
+    /// Advance the sequencer by one frame and add the controls it plays to its channel
+    fn tick(&self, channel_num: u8, state: &State, cache: &mut FrameCache) {
+        let swing = state
+            .resolve_dynamic_value(&self.swing, channel_num, cache)
+            .unwrap_or(0.0);
+        let gate = state
+            .resolve_dynamic_value(&self.gate, channel_num, cache)
+            .unwrap_or(0.5);
+        let mut controls: Vec<Control> = cache.channel_controls(channel_num).collect();
+        let mut playing = self.playing.lock();
+        // End notes whose time is up
+        playing.gates.tick(state.vars.tempo, &mut controls);
+        // Play the current step
+        let (len, step) = playing.clock.tick(state, self.step, self.division, swing);
+        if let Some(step) = step {
+            for track in &self.tracks {
+                let hits = &track.pattern.0;
+                if hits.is_empty() || !hits[(step % hits.len() as u64) as usize] {
+                    continue;
+                }
+                match track.target {
+                    StepTarget::Pad(pad) => controls.push(Control::Pad(pad, track.velocity)),
+                    StepTarget::Note(note) => {
+                        playing
+                            .gates
+                            .start(len * gate, note, track.velocity, &mut controls)
+                    }
+                }
+            }
+        }
+        cache.set_channel_controls(channel_num, controls);
+    }
+}
+
+/// The playing state of an arpeggiator
+#[derive(Debug, Clone, Default)]
+struct ArpState {
+    clock: StepClock,
+    gates: NoteGates,
+    /// Id, note, and velocity of each held note in the order they were played
+    held: Vec<(u64, u8, u8)>,
+    /// The number of steps played since the first note was held
+    position: usize,
+}
+
+/// An arpeggiator
+#[derive(Debug, Clone)]
+pub struct Arp {
+    /// The order in which held notes are played
+    pub mode: ArpMode,
+    /// The length of a step in seconds
+    pub step: f32,
+    /// The number of steps in one period of the loop master
+    pub division: Option<u16>,
+    /// The number of octaves the held notes are repeated over
+    pub octaves: u8,
+    /// The fraction of a step that notes are held for
+    pub gate: DynamicValue,
+    playing: CloneLock<ArpState>,
+}
+
+impl Arp {
+    /// Create a new arpeggiator
+    #[must_use]
+    pub fn new() -> Self {
+        Arp {
+            mode: ArpMode::default(),
+            step: 0.125,
+            division: None,
+            octaves: 1,
+            gate: DynamicValue::Static(0.5),
+            playing: CloneLock::new(ArpState::default()),
+        }
+    }
+    /// Get the notes and velocities of one cycle of the arpeggio
+    fn arpeggio(&self, held: &[(u64, u8, u8)]) -> Vec<(u8, u8)> {
+        let mut notes: Vec<(u8, u8)> = held.iter().map(|&(_, note, vel)| (note, vel)).collect();
+        if self.mode != ArpMode::Played {
+            notes.sort_by_key(|&(note, _)| note);
+            notes.dedup_by_key(|&mut (note, _)| note);
+        }
+        let mut arpeggio: Vec<(u8, u8)> = (0..u16::from(self.octaves.max(1)))
+            .flat_map(|octave| {
+                notes.iter().filter_map(move |&(note, vel)| {
+                    let note = u16::from(note) + 12 * octave;
+                    if note <= 127 {
+                        Some((note as u8, vel))
+                    } else {
+                        None
+                    }
+                })
+            })
+            .collect();
+        match self.mode {
+            ArpMode::Down => arpeggio.reverse(),
+            ArpMode::UpDown if arpeggio.len() > 2 => {
+                let down: Vec<(u8, u8)> = arpeggio[1..arpeggio.len() - 1]
+                    .iter()
+                    .rev()
+                    .copied()
+                    .collect();
+                arpeggio.extend(down);
+            }
+            _ => {}
+        }
+        arpeggio
+    }
+    /// Replace the held notes in the channel's controls with the arpeggio
+    fn process(&self, channel_num: u8, state: &State, cache: &mut FrameCache) {
+        let gate = state
+            .resolve_dynamic_value(&self.gate, channel_num, cache)
+            .unwrap_or(0.5);
+        let mut playing = self.playing.lock();
+        let mut controls = Vec::new();
+        for control in cache.channel_controls(channel_num) {
+            match control {
+                Control::NoteStart(id, note, vel) => {
+                    // Start the arpeggio immediately
+                    if playing.held.is_empty() {
+                        playing.clock = StepClock::default();
+                        playing.position = 0;
+                    }
+                    playing.held.push((id, note, vel));
+                }
+                Control::NoteEnd(id, note) => playing
+                    .held
+                    .retain(|&(held_id, held_note, _)| held_id != id || held_note != note),
+                control => controls.push(control),
+            }
+        }
+        // Only advance once per frame
+        if !cache.from_loop {
+            playing.gates.tick(state.vars.tempo, &mut controls);
+            let (len, step) = playing.clock.tick(state, self.step, self.division, 0.0);
+            if step.is_some() {
+                let arpeggio = self.arpeggio(&playing.held);
+                if !arpeggio.is_empty() {
+                    let index = if self.mode == ArpMode::Random {
+                        random::<usize>() % arpeggio.len()
+                    } else {
+                        playing.position % arpeggio.len()
+                    };
+                    playing.position += 1;
+                    let (note, vel) = arpeggio[index];
+                    playing.gates.start(len * gate, note, vel, &mut controls);
+                }
+            }
+        }
+        cache.set_channel_controls(channel_num, controls);
+    }
+}
+
+/// A scale lock and chord generator
+#[derive(Debug, Clone)]
+pub struct NoteProcessor {
+    /// The semitones of each degree of the scale above the root
+    pub steps: Vec<u8>,
+    /// The pitch class of the scale's root
+    pub root: u8,
+    /// The chord that each note is expanded into
+    pub chord: Option<ChordDef>,
+    /// The ids and notes played for each original id
+    playing: CloneLock<HashMap<u64, Vec<(u64, u8)>>>,
+}
+
+impl NoteProcessor {
+    /// Create a new note processor
+    #[must_use]
+    pub fn new() -> Self {
+        NoteProcessor {
+            steps: Scale::Chromatic.steps().to_vec(),
+            root: 0,
+            chord: None,
+            playing: CloneLock::new(HashMap::new()),
+        }
+    }
+    /// Get the id of a note derived from another
+    fn derive_id(id: u64, index: usize) -> u64 {
+        id.wrapping_add((index as u64) << 32)
+    }
+    /// Get the number of notes each played note is expanded into
+    fn chord_len(&self) -> usize {
+        match &self.chord {
+            Some(ChordDef::Named(chord)) => chord.degrees().len(),
+            Some(ChordDef::Custom(intervals)) => intervals.len().max(1),
+            None => 1,
+        }
+    }
+    /// Get the note of a scale degree, where degree 0 is the root of the given octave
+    fn degree_note(steps: &[u8], root: i16, octave: i16, degree: i16) -> i16 {
+        let len = steps.len() as i16;
+        let octave = octave + degree.div_euclid(len);
+        root + octave * 12 + i16::from(steps[degree.rem_euclid(len) as usize])
+    }
+    /// Lock a note to the scale
+    ///
+    /// Returns the octave and scale degree of the nearest note in the scale
+    fn lock(&self, note: u8) -> (i16, i16) {
+        let relative = i16::from(note) - i16::from(self.root % 12);
+        let (octave, pitch_class) = (relative.div_euclid(12), relative.rem_euclid(12));
+        // Ties go to the lower note
+        let degree = self
+            .steps
+            .iter()
+            .map(|&step| i16::from(step))
+            .chain(Some(12))
+            .enumerate()
+            .min_by_key(|&(_, step)| (step - pitch_class).abs())
+            .map_or(0, |(degree, _)| degree as i16);
+        (octave, degree)
+    }
+    /// Get the notes that a played note is turned into
+    fn notes(&self, note: u8) -> Vec<Option<u8>> {
+        let root = i16::from(self.root % 12);
+        let (octave, degree) = self.lock(note);
+        let locked = Self::degree_note(&self.steps, root, octave, degree);
+        let notes: Vec<i16> = match &self.chord {
+            None => vec![locked],
+            Some(ChordDef::Named(chord)) => {
+                if self.steps.len() == 12 {
+                    // Use major chords with the chromatic scale
+                    let major = Scale::Major.steps();
+                    chord
+                        .degrees()
+                        .iter()
+                        .map(|&d| Self::degree_note(major, locked, 0, i16::from(d)))
+                        .collect()
+                } else {
+                    chord
+                        .degrees()
+                        .iter()
+                        .map(|&d| {
+                            Self::degree_note(&self.steps, root, octave, degree + i16::from(d))
+                        })
+                        .collect()
+                }
+            }
+            Some(ChordDef::Custom(intervals)) if intervals.is_empty() => vec![locked],
+            Some(ChordDef::Custom(intervals)) => intervals
+                .iter()
+                .map(|&interval| locked + i16::from(interval))
+                .collect(),
+        };
+        notes
+            .into_iter()
+            .map(|note| {
+                if (0..=127).contains(&note) {
+                    Some(note as u8)
+                } else {
+                    None
+                }
+            })
+            .collect()
+    }
+    /// Remap the notes in the channel's controls
+    fn process(&self, channel_num: u8, cache: &mut FrameCache) {
+        let mut playing = self.playing.lock();
+        let mut controls = Vec::new();
+        for control in cache.channel_controls(channel_num) {
+            match control {
+                Control::NoteStart(id, note, vel) => {
+                    let notes: Vec<(u64, u8)> = self
+                        .notes(note)
+                        .into_iter()
+                        .enumerate()
+                        .filter_map(|(i, note)| note.map(|note| (Self::derive_id(id, i), note)))
+                        .collect();
+                    for &(id, note) in &notes {
+                        controls.push(Control::NoteStart(id, note, vel));
+                    }
+                    playing.insert(id, notes);
+                }
+                Control::NoteEnd(id, _) if playing.contains_key(&id) => {
+                    for (id, note) in playing.remove(&id).unwrap_or_default() {
+                        controls.push(Control::NoteEnd(id, note));
+                    }
+                }
+                control => controls.push(control),
+            }
+        }
+        cache.set_channel_controls(channel_num, controls);
+    }
+}
+
 impl Node {
     pub fn next(
         &self,
@@ -256,7 +1273,7 @@ impl Node {
                     .resolve_dynamic_value(&wave.sustain_pedal, channel_num, cache)
                     .unwrap_or(0.0)
                     != 0.0;
-                enveloper.register(cache.channel_controls(channel_num), sustain);
+                enveloper.register(cache.channel_controls(channel_num), sustain, wave.voicing);
                 let adsr = wave
                     .adsr
                     .map_or_default(|value| state.resolve_dynamic_value(value, channel_num, cache));
@@ -267,10 +1284,11 @@ impl Node {
                     .envelopes()
                     .zip(&mut *waves)
                     .map(|(env_frame, i)| {
-                        let (letter, octave) = Letter::from_u8(env_frame.note);
-                        let freq = letter.freq(
-                            (i16::from(octave) + i16::from(wave.octave.unwrap_or(0))).max(0) as u8,
-                        ) * 2_f32.powf(env_frame.pitch_bend * pitch_bend_range / 12.0);
+                        let freq = wave.tuning.freq(env_frame.pitch)
+                            * 2_f32.powf(
+                                f32::from(wave.octave.unwrap_or(0))
+                                    + env_frame.pitch_bend * pitch_bend_range / 12.0,
+                            );
                         if freq == 0.0 {
                             return Voice::SILENT;
                         }
@@ -296,8 +1314,11 @@ impl Node {
                         Voice::mono(s)
                     })
                     .fold(Voice::SILENT, |acc, v| acc + v);
+                let glide = state
+                    .resolve_dynamic_value(&wave.glide, channel_num, cache)
+                    .unwrap_or(0.0);
 
-                enveloper.progress(state.vars.sample_rate, adsr, sustain);
+                enveloper.progress(state.vars.sample_rate, adsr, sustain, glide);
                 voice
             }
             // Drum Machine
@@ -308,23 +1329,63 @@ impl Node {
                     if let Control::Pad(i, v) = control {
                         let index = i as usize;
                         if index < drums.samples.len() {
+                            let pad = drums.pad(index);
+                            // Choke other pads in the same group
+                            if let Some(group) = pad.choke {
+                                for sampling in samplings.iter_mut() {
+                                    if drums.pad(sampling.index).choke == Some(group) {
+                                        sampling.choke_fade.get_or_insert(1.0);
+                                    }
+                                }
+                            }
+                            let pos = state
+                                .sample_bank
+                                .get(&drums.samples[index])
+                                .finished()
+                                .and_then(|res| {
+                                    res.as_ref().ok().map(|sample| {
+                                        f64::from(pad.start) * f64::from(sample.sample_rate())
+                                    })
+                                })
+                                .unwrap_or(0.0);
                             samplings.push(ActiveSampling {
                                 index,
-                                i: 0,
+                                pos,
                                 velocity: f32::from(v) / 127.0,
+                                choke_fade: None,
                             });
                         }
                     }
                 }
                 // Mix currently playing samples
+                let choke_step = 1.0 / (CHOKE_FADE_TIME * state.vars.sample_rate as f32);
                 let mut mixed = Voice::SILENT;
                 for ms in (0..samplings.len()).rev() {
-                    let ActiveSampling { index, i, velocity } = &mut samplings[ms];
+                    let ActiveSampling {
+                        index,
+                        pos,
+                        velocity,
+                        choke_fade,
+                    } = &mut samplings[ms];
                     if let Some(res) = state.sample_bank.get(&drums.samples[*index]).finished() {
                         if let Ok(sample) = &*res {
-                            if *i < sample.len(state.vars.sample_rate) {
-                                mixed += sample.voice(*i, state.vars.sample_rate) * *velocity;
-                                *i += 1;
+                            let pad = drums.pad(*index);
+                            let len = sample.len(sample.sample_rate()) as f64;
+                            let fade = choke_fade.unwrap_or(1.0);
+                            if *pos < len && fade > 0.0 {
+                                let frame_pos = if pad.reverse { len - 1.0 - *pos } else { *pos };
+                                let pan = pan_gains(pad.pan);
+                                mixed += sample.voice_at_frame(frame_pos, drums.interpolation)
+                                    * pan
+                                    * pad.gain
+                                    * *velocity
+                                    * fade;
+                                *pos += f64::from(sample.sample_rate())
+                                    / f64::from(state.vars.sample_rate)
+                                    * f64::from(2_f32.powf(pad.tune / 12.0));
+                                if let Some(fade) = choke_fade {
+                                    *fade -= choke_step;
+                                }
                             } else {
                                 samplings.remove(ms);
                             }
@@ -344,7 +1405,11 @@ impl Node {
                         .unwrap_or(0.0)
                         != 0.0;
                     let mut enveloper = filter.enveloper.lock();
-                    enveloper.register(cache.channel_controls(channel_num), sustain);
+                    enveloper.register(
+                        cache.channel_controls(channel_num),
+                        sustain,
+                        filter.voicing,
+                    );
                     let adsr = adsr.map_or_default(|value| {
                         state.resolve_dynamic_value(value, channel_num, cache)
                     });
@@ -353,7 +1418,7 @@ impl Node {
                         .map(|env_frame| Float(env_frame.amplitude))
                         .max()
                         .map(|f| f.0);
-                    enveloper.progress(state.vars.sample_rate, adsr, sustain);
+                    enveloper.progress(state.vars.sample_rate, adsr, sustain, 0.0);
                     value
                 } else {
                     state.resolve_dynamic_value(&filter.value, channel_num, cache)
@@ -381,23 +1446,118 @@ impl Node {
                         }
                         (output.unwrap_or(frame) + frame) * 0.5
                     }
-                    FilterState::Crush { counter, voice } => {
-                        let delay_frames = value.map_or(0, |val| (val * 0x7f as f32) as usize);
-                        if counter.load() == 0 {
-                            counter.store(delay_frames);
-                            voice.store(frame);
-                            frame
+                    FilterState::Crush {
+                        phase,
+                        voice,
+                        anti_alias,
+                    } => {
+                        // Without a rate, the value determines how many frames each sample is held
+                        let rate = if let Some(rate) = &filter.rate {
+                            state.resolve_or(rate, 1.0, channel_num, cache)
                         } else {
-                            counter.store(counter.load() - 1);
-                            voice.load()
+                            1.0 / (value.map_or(0.0, |val| (val * 0x7f as f32).floor()) + 1.0)
                         }
+                        .max(0.001)
+                        .min(1.0);
+                        let bits = state
+                            .resolve_or(&filter.bits, 24.0, channel_num, cache)
+                            .max(1.0)
+                            .min(24.0);
+                        let mix = state.resolve_or(&filter.mix, 1.0, channel_num, cache);
+                        // Remove frequencies that the reduced rate can not represent
+                        let input = if filter.anti_alias {
+                            let sample_rate = state.vars.sample_rate;
+                            let coefficients = BiquadCoefficients::new(
+                                BiquadShape::LowPass,
+                                sample_rate,
+                                rate * sample_rate as f32 * 0.45,
+                                0.707,
+                                0.0,
+                            );
+                            let mut biquad = anti_alias.load();
+                            let output = biquad.process(&coefficients, frame);
+                            anti_alias.store(biquad);
+                            output
+                        } else {
+                            frame
+                        };
+                        // Take a new sample each time the phase passes 1
+                        let new_phase = phase.load() + rate;
+                        if new_phase >= 1.0 {
+                            let levels = 2f32.powf(bits - 1.0);
+                            let quantize = |x: f32| (x * levels).round() / levels;
+                            voice.store(Voice::stereo(quantize(input.left), quantize(input.right)));
+                        }
+                        phase.store(new_phase.fract());
+                        dsp::mix(frame, voice.load(), mix)
                     }
-                    FilterState::Distortion => {
+                    FilterState::Distortion { oversampler, tone } => {
+                        // Lower values lower the clipping threshold, which is made up for with gain
                         let threshold = value.unwrap_or(1.0).max(0.01).powf(2.0);
-                        Voice::stereo(
-                            frame.left.max(-threshold).min(threshold),
-                            frame.right.max(-threshold).min(threshold),
-                        ) * (1.0 / threshold)
+                        let gain =
+                            state.resolve_or(&filter.drive, 1.0, channel_num, cache) / threshold;
+                        let cutoff =
+                            cutoff(state.resolve_or(&filter.tone, 1.0, channel_num, cache));
+                        let mix = state.resolve_or(&filter.mix, 1.0, channel_num, cache);
+                        let sample_rate = state.vars.sample_rate;
+                        let factor = match filter.oversample {
+                            0 | 1 => 1,
+                            2 | 3 => 2,
+                            _ => 4,
+                        };
+                        let curve = &filter.curve;
+                        let mut os = oversampler.load();
+                        let shaped = os.process(sample_rate, factor, frame, |frame| {
+                            Voice::stereo(
+                                dsp::waveshape(curve, frame.left * gain),
+                                dsp::waveshape(curve, frame.right * gain),
+                            )
+                        });
+                        oversampler.store(os);
+                        // The tone filter is bypassed when fully open
+                        let wet = if cutoff < MIN_CUTOFF * CUTOFF_RANGE {
+                            let coefficients = BiquadCoefficients::new(
+                                BiquadShape::LowPass,
+                                sample_rate,
+                                cutoff,
+                                0.707,
+                                0.0,
+                            );
+                            let mut biquad = tone.load();
+                            let output = biquad.process(&coefficients, shaped);
+                            tone.store(biquad);
+                            output
+                        } else {
+                            shaped
+                        };
+                        dsp::mix(frame, wet, mix)
+                    }
+                    FilterState::Biquad(shape, biquad) => {
+                        let cutoff = cutoff(value.unwrap_or(1.0));
+                        let mut q = state
+                            .resolve_dynamic_value(&filter.resonance, channel_num, cache)
+                            .unwrap_or(0.707);
+                        // With an envelope, the value is the envelope's level
+                        if filter.adsr.is_some() {
+                            q += state
+                                .resolve_dynamic_value(&filter.resonance_env, channel_num, cache)
+                                .unwrap_or(0.0)
+                                * value.unwrap_or(0.0);
+                        }
+                        let gain = state
+                            .resolve_dynamic_value(&filter.gain, channel_num, cache)
+                            .unwrap_or(0.0);
+                        let coefficients = BiquadCoefficients::new(
+                            *shape,
+                            state.vars.sample_rate,
+                            cutoff,
+                            q,
+                            gain,
+                        );
+                        let mut filter = biquad.load();
+                        let output = filter.process(&coefficients, frame);
+                        biquad.store(filter);
+                        output
                     }
                 }
             }
@@ -412,33 +1572,264 @@ impl Node {
                     .resolve_dynamic_value(&bal.pan, channel_num, cache)
                     .unwrap_or(0.0);
 
-                let pan =
-                    Voice::stereo((1.0 + pan).min(1.0).max(0.0), (1.0 - pan).min(1.0).max(0.0));
-
-                frame * pan * volume
+                frame * pan_gains(pan) * volume
+            }
+            // Mixer
+            Node::Mixer(mixer) => {
+                let mut sum = Voice::SILENT;
+                for input in &mixer.inputs {
+                    let frame = channel.next_from(channel_num, &input.input, state, cache);
+                    let gain = state
+                        .resolve_dynamic_value(&input.gain, channel_num, cache)
+                        .unwrap_or(1.0);
+                    let pan = state
+                        .resolve_dynamic_value(&input.pan, channel_num, cache)
+                        .unwrap_or(0.0);
+                    sum += frame * pan_gains(pan) * gain;
+                }
+                sum
             }
             // Reverb
             Node::Reverb(reverb) => {
                 let input_frame = channel.next_from(channel_num, &reverb.input, state, cache);
-                let size = state
-                    .resolve_dynamic_value(&reverb.size, channel_num, cache)
-                    .unwrap_or(1.0)
-                    * 10.0;
-                let energy_mul = state
-                    .resolve_dynamic_value(&reverb.energy_mul, channel_num, cache)
-                    .unwrap_or(0.5);
-                let return_time = size * 2.0 / SPEED_OF_SOUND;
-                let return_frame_count = (return_time * state.vars.sample_rate as f32) as usize;
-                let mut frames = reverb.frames.lock();
-                let mut reverbed = Voice::SILENT;
-                while frames.len() > return_frame_count {
-                    if let Some(voice) = frames.pop_front() {
-                        reverbed = voice;
+                let size = state.resolve_or(&reverb.size, 1.0, channel_num, cache);
+                let decay = state.resolve_or(&reverb.decay, 0.5, channel_num, cache);
+                let damping = state.resolve_or(&reverb.damping, 0.5, channel_num, cache);
+                let pre_delay = state.resolve_or(&reverb.pre_delay, 0.0, channel_num, cache);
+                let mix = state.resolve_or(&reverb.mix, 0.5, channel_num, cache);
+                let sample_rate = state.vars.sample_rate;
+                let mut lines = reverb.lines.lock();
+                lines.pre_delay.push(input_frame);
+                let delayed = lines
+                    .pre_delay
+                    .read(pre_delay.max(0.0).min(MAX_PRE_DELAY) * sample_rate as f32 + 1.0);
+                let wet = lines.freeverb.process(delayed, size, decay, damping);
+                dsp::mix(input_frame, wet, mix)
+            }
+            // Delay
+            Node::Delay(delay) => {
+                let input_frame = channel.next_from(channel_num, &delay.input, state, cache);
+                let time = state.resolve_or(&delay.time, 0.25, channel_num, cache);
+                let feedback = state
+                    .resolve_or(&delay.feedback, 0.4, channel_num, cache)
+                    .max(0.0)
+                    .min(0.95);
+                let tone = state.resolve_or(&delay.tone, 1.0, channel_num, cache);
+                let mix = state.resolve_or(&delay.mix, 0.5, channel_num, cache);
+                let sample_rate = state.vars.sample_rate;
+                // Determine the delay in frames
+                let frames = match (delay.division, state.loop_master) {
+                    (Some(division), Some(master)) if division > 0.0 => {
+                        master.period / division / state.vars.tempo.max(0.01)
+                    }
+                    _ => time * sample_rate as f32,
+                };
+                let mut lines = delay.lines.lock();
+                let echo = lines.line.read(frames);
+                let coefficients = BiquadCoefficients::new(
+                    BiquadShape::LowPass,
+                    sample_rate,
+                    cutoff(tone),
+                    0.707,
+                    0.0,
+                );
+                let fed_back = lines.filter.process(&coefficients, echo) * feedback;
+                lines.line.push(if delay.ping_pong {
+                    // Send the input into the left channel and swap channels on each echo
+                    Voice::stereo(
+                        (input_frame.left + input_frame.right) * 0.5 + fed_back.right,
+                        fed_back.left,
+                    )
+                } else {
+                    input_frame + fed_back
+                });
+                dsp::mix(input_frame, echo, mix)
+            }
+            // Modulation
+            Node::Modulation(modulation) => {
+                let input_frame = channel.next_from(channel_num, &modulation.input, state, cache);
+                let rate = state.resolve_or(&modulation.rate, 0.5, channel_num, cache);
+                let depth = state
+                    .resolve_or(&modulation.depth, 0.5, channel_num, cache)
+                    .max(0.0)
+                    .min(1.0);
+                let feedback = state
+                    .resolve_or(&modulation.feedback, 0.0, channel_num, cache)
+                    .max(-0.95)
+                    .min(0.95);
+                let mix = state.resolve_or(&modulation.mix, 0.5, channel_num, cache);
+                let sample_rate = state.vars.sample_rate;
+                let mut lines = modulation.lines.lock();
+                // The LFO for each channel, where the right channel is a quarter cycle ahead
+                let lfo = |offset: f32| ((lines.phase + offset) * 2.0 * PI).sin() * 0.5 + 0.5;
+                let (lfo_left, lfo_right) = (lfo(0.0), lfo(0.25));
+                lines.phase = (lines.phase + rate / sample_rate as f32).fract();
+                let input = input_frame + lines.last_output * feedback;
+                let wet = match modulation.mode {
+                    ModulationMode::Chorus | ModulationMode::Flanger => {
+                        // Base delay and sweep range in seconds
+                        let (base, sweep) = if modulation.mode == ModulationMode::Chorus {
+                            (0.02, 0.01)
+                        } else {
+                            (0.001, 0.005)
+                        };
+                        let delay = |lfo: f32| (base + sweep * depth * lfo) * sample_rate as f32;
+                        lines.line.push(input);
+                        let left = lines.line.read(delay(lfo_left)).left;
+                        let right = lines.line.read(delay(lfo_right)).right;
+                        Voice::stereo(left, right)
+                    }
+                    ModulationMode::Phaser => {
+                        // Sweep the allpass frequencies exponentially
+                        let freq = |lfo: f32| 200.0 * 20_f32.powf(depth * lfo);
+                        let left_coef = AllpassStage::coefficient(freq(lfo_left), sample_rate);
+                        let right_coef = AllpassStage::coefficient(freq(lfo_right), sample_rate);
+                        lines.stages.iter_mut().fold(input, |acc, (left, right)| {
+                            Voice::stereo(
+                                left.process(left_coef, acc.left),
+                                right.process(right_coef, acc.right),
+                            )
+                        })
+                    }
+                };
+                lines.last_output = wet;
+                dsp::mix(input_frame, wet, mix)
+            }
+            // Pitch shift
+            Node::PitchShift(shift) => {
+                let input_frame = channel.next_from(channel_num, &shift.input, state, cache);
+                let offset = state.resolve_or(&shift.semitones, 0.0, channel_num, cache)
+                    + state.resolve_or(&shift.cents, 0.0, channel_num, cache) / 100.0;
+                let mix = state.resolve_or(&shift.mix, 0.5, channel_num, cache);
+                let sample_rate = state.vars.sample_rate;
+                let mut shifters = shift.shifters.lock();
+                let ratio = |semitones: f32| 2f32.powf(semitones / 12.0);
+                let wet = match shift.mode {
+                    PitchShiftMode::Shift => shifters.shifter.process(input_frame, ratio(offset)),
+                    PitchShiftMode::Harmonize => {
+                        // Start and release voices for held notes
+                        for control in cache.channel_controls(channel_num) {
+                            match control {
+                                Control::NoteStart(id, note, vel) => {
+                                    shifters.harmonies.insert(
+                                        id,
+                                        Harmony {
+                                            note,
+                                            velocity: f32::from(vel) / 127.0,
+                                            gain: 0.0,
+                                            released: false,
+                                            shifter: PitchShifter::new(PitchShiftState::window(
+                                                sample_rate,
+                                            )),
+                                        },
+                                    );
+                                }
+                                Control::NoteEnd(id, _) => {
+                                    if let Some(harmony) = shifters.harmonies.get_mut(&id) {
+                                        harmony.released = true;
+                                    }
+                                }
+                                _ => {}
+                            }
+                        }
+                        let fade = 1.0 / (HARMONY_FADE_TIME * sample_rate as f32);
+                        let root = shift.root;
+                        let mut wet = Voice::SILENT;
+                        for harmony in shifters.harmonies.values_mut() {
+                            harmony.gain = if harmony.released {
+                                (harmony.gain - fade).max(0.0)
+                            } else {
+                                (harmony.gain + fade).min(1.0)
+                            };
+                            let semitones = f32::from(harmony.note) - f32::from(root) + offset;
+                            wet += harmony.shifter.process(input_frame, ratio(semitones))
+                                * (harmony.gain * harmony.velocity);
+                        }
+                        shifters
+                            .harmonies
+                            .retain(|_, harmony| !harmony.released || harmony.gain > 0.0);
+                        wet
                     }
+                };
+                dsp::mix(input_frame, wet, mix)
+            }
+            // Gate
+            Node::Gate(gate) => {
+                let frame = channel.next_from(channel_num, &gate.input, state, cache);
+                let threshold = state.resolve_or(&gate.threshold, -40.0, channel_num, cache);
+                let attack = state.resolve_or(&gate.attack, 0.001, channel_num, cache);
+                let hold = state.resolve_or(&gate.hold, 0.05, channel_num, cache);
+                let release = state.resolve_or(&gate.release, 0.1, channel_num, cache);
+                let sample_rate = state.vars.sample_rate;
+                let mut gate_state = gate.state.load();
+                // Keep the gate open while the level is above the threshold and for the hold time after
+                let level = dsp::amp_to_db(frame.left.abs().max(frame.right.abs()));
+                if level >= threshold {
+                    gate_state.hold = (hold.max(0.0) * sample_rate as f32) as u32;
+                } else {
+                    gate_state.hold = gate_state.hold.saturating_sub(1);
                 }
-                let output = input_frame + reverbed;
-                frames.push_back(output * energy_mul);
-                output
+                let open = level >= threshold || gate_state.hold > 0;
+                let (target, time) = if open { (1.0, attack) } else { (0.0, release) };
+                let coef = dsp::smoothing_coefficient(time, sample_rate);
+                gate_state.gain = target + (gate_state.gain - target) * coef;
+                gate.state.store(gate_state);
+                frame * gate_state.gain
+            }
+            // Follower
+            Node::Follower(follower) => {
+                let frame = channel.next_from(channel_num, &follower.input, state, cache);
+                let attack = state.resolve_or(&follower.attack, 0.01, channel_num, cache);
+                let release = state.resolve_or(&follower.release, 0.2, channel_num, cache);
+                let gain = state.resolve_or(&follower.gain, 1.0, channel_num, cache);
+                let level = frame.left.abs().max(frame.right.abs());
+                let envelope = follower.envelope.load();
+                let time = if level > envelope { attack } else { release };
+                let coef = dsp::smoothing_coefficient(time, state.vars.sample_rate);
+                let envelope = level + (envelope - level) * coef;
+                follower.envelope.store(envelope);
+                Voice::mono(envelope * gain)
+            }
+            // Equalizer
+            Node::Equalizer(eq) => {
+                let mut frame = channel.next_from(channel_num, &eq.input, state, cache);
+                let sample_rate = state.vars.sample_rate;
+                let mut filters = eq.filters.lock();
+                for (band, filter) in eq.bands.iter().zip(filters.iter_mut()) {
+                    let freq = state.resolve_or(&band.freq, 1000.0, channel_num, cache);
+                    let gain = state.resolve_or(&band.gain, 0.0, channel_num, cache);
+                    let q = state.resolve_or(&band.q, 0.707, channel_num, cache);
+                    let coefficients =
+                        BiquadCoefficients::new(eq_shape(band.shape), sample_rate, freq, q, gain);
+                    frame = filter.process(&coefficients, frame);
+                }
+                frame
+            }
+            // Compressor
+            Node::Compressor(comp) => {
+                let input_frame = channel.next_from(channel_num, &comp.input, state, cache);
+                let detect_frame = if let Some(sidechain) = &comp.sidechain {
+                    channel.next_from(channel_num, sidechain, state, cache)
+                } else {
+                    input_frame
+                };
+                let threshold = state.resolve_or(&comp.threshold, -18.0, channel_num, cache);
+                let ratio = state.resolve_or(&comp.ratio, 4.0, channel_num, cache);
+                let attack = state.resolve_or(&comp.attack, 0.01, channel_num, cache);
+                let release = state.resolve_or(&comp.release, 0.1, channel_num, cache);
+                let knee = state
+                    .resolve_or(&comp.knee, 6.0, channel_num, cache)
+                    .max(0.0);
+                let makeup = state.resolve_or(&comp.makeup, 0.0, channel_num, cache);
+                let level = dsp::amp_to_db(detect_frame.left.abs().max(detect_frame.right.abs()));
+                let target = dsp::compression_gain(level, threshold, ratio, knee);
+                // Smooth the gain reduction, using the attack time when it is increasing
+                let current = comp.reduction.load();
+                let time = if target < current { attack } else { release };
+                let coef = dsp::smoothing_coefficient(time, state.vars.sample_rate);
+                let reduction = target + (current - target) * coef;
+                comp.reduction.store(reduction);
+                input_frame * dsp::db_to_amp(reduction + makeup)
             }
             // Sampler
             Node::Sampler(sampler) => {
@@ -447,32 +1838,47 @@ impl Node {
                     .resolve_dynamic_value(&sampler.sustain_pedal, channel_num, cache)
                     .unwrap_or(0.0)
                     != 0.0;
-                enveloper.register(cache.channel_controls(channel_num), sustain);
+                enveloper.register(
+                    cache.channel_controls(channel_num),
+                    sustain,
+                    sampler.voicing,
+                );
                 let adsr = sampler
                     .adsr
                     .map_or_default(|value| state.resolve_dynamic_value(value, channel_num, cache));
-                let output = enveloper
-                    .envelopes()
-                    .fold(Voice::SILENT, |mut acc, env_frame| {
-                        if let Some(sample) = state.sample_bank.get(&sampler.def.path).finished() {
-                            if let Ok(sample) = &*sample {
-                                let (l, o) = Letter::from_u8(env_frame.note);
-                                let freq = l.freq(o);
-                                let t = env_frame.t * freq / sampler.def.pitch;
-                                let t = if t < sampler.def.loop_start {
-                                    t
-                                } else {
-                                    sampler.def.loop_start
-                                        + (t - sampler.def.loop_start)
-                                            % (sample.dur_seconds() - sampler.def.loop_start)
-                                };
-                                let voice = sample.voice_at_time(t, state.vars.sample_rate);
-                                acc += voice * env_frame.amplitude;
-                            }
+                let sample_rate = state.vars.sample_rate;
+                let mut output = Voice::SILENT;
+                let mut phases = Vec::new();
+                for env_frame in enveloper.envelopes() {
+                    let freq = sampler.tuning.freq(env_frame.pitch);
+                    if freq == 0.0 {
+                        continue;
+                    }
+                    // The phase counts the cycles played at the voice's pitch
+                    let phase = env_frame.phase(0);
+                    phases.push((env_frame.id, phase + f64::from(freq / sample_rate as f32)));
+                    if let Some(sample) = state.sample_bank.get(&sampler.def.path).finished() {
+                        if let Ok(sample) = &*sample {
+                            let t = phase as f32 / sampler.def.pitch;
+                            let t = if t < sampler.def.loop_start {
+                                t
+                            } else {
+                                sampler.def.loop_start
+                                    + (t - sampler.def.loop_start)
+                                        % (sample.dur_seconds() - sampler.def.loop_start)
+                            };
+                            let voice = sample.voice_at_time(t, sampler.interpolation);
+                            output += voice * env_frame.amplitude;
                         }
-                        acc
-                    });
-                enveloper.progress(state.vars.sample_rate, adsr, sustain);
+                    }
+                }
+                for (id, phase) in phases {
+                    enveloper.set_phase(id, 0, phase);
+                }
+                let glide = state
+                    .resolve_dynamic_value(&sampler.glide, channel_num, cache)
+                    .unwrap_or(0.0);
+                enveloper.progress(state.vars.sample_rate, adsr, sustain, glide);
                 output
             }
             // InputPass
@@ -481,11 +1887,197 @@ impl Node {
                 .get(&pass.input)
                 .copied()
                 .unwrap_or(Voice::SILENT),
+            // Bus send
+            Node::BusSend(send) => {
+                let frame = channel.next_from(channel_num, &send.input, state, cache);
+                let level = state
+                    .resolve_dynamic_value(&send.level, channel_num, cache)
+                    .unwrap_or(1.0);
+                *cache.sends.entry(send.bus).or_insert(Voice::SILENT) += frame * level;
+                frame
+            }
+            // Bus return
+            Node::BusReturn(_) => cache
+                .sends
+                .get(&channel_num)
+                .copied()
+                .unwrap_or(Voice::SILENT),
+            // Control processors do not output audio
+            Node::Sequencer(_) | Node::Arp(_) | Node::NoteProcessor(_) => Voice::SILENT,
+            // Pluck
+            Node::Pluck(pluck) => {
+                let mut enveloper = pluck.enveloper.lock();
+                let sustain = state
+                    .resolve_dynamic_value(&pluck.sustain_pedal, channel_num, cache)
+                    .unwrap_or(0.0)
+                    != 0.0;
+                enveloper.register(cache.channel_controls(channel_num), sustain, pluck.voicing);
+                let adsr = pluck
+                    .adsr
+                    .map_or_default(|value| state.resolve_dynamic_value(value, channel_num, cache));
+                let pitch_bend_range = state
+                    .resolve_dynamic_value(&pluck.pitch_bend_range, channel_num, cache)
+                    .unwrap_or(12.0);
+                let brightness = state
+                    .resolve_dynamic_value(&pluck.brightness, channel_num, cache)
+                    .unwrap_or(0.5)
+                    .max(0.0)
+                    .min(1.0);
+                let decay = state
+                    .resolve_dynamic_value(&pluck.decay, channel_num, cache)
+                    .unwrap_or(2.0)
+                    .max(0.001);
+                let sample_rate = state.vars.sample_rate as f32;
+                let mut strings = pluck.strings.lock();
+                // Forget the strings of finished voices
+                let ids: HashSet<u64> = enveloper
+                    .envelopes()
+                    .map(|env_frame| env_frame.id)
+                    .collect();
+                strings.retain(|id, _| ids.contains(id));
+                let voice = enveloper.envelopes().fold(Voice::SILENT, |acc, env_frame| {
+                    let freq = pluck.tuning.freq(env_frame.pitch)
+                        * 2_f32.powf(
+                            f32::from(pluck.octave)
+                                + env_frame.pitch_bend * pitch_bend_range / 12.0,
+                        );
+                    if freq == 0.0 {
+                        return acc;
+                    }
+                    let period = sample_rate / freq;
+                    let feedback = 0.001_f32.powf(1.0 / (decay * freq));
+                    let string = strings
+                        .entry(env_frame.id)
+                        .or_insert_with(|| PluckString::new(period, brightness));
+                    acc + Voice::mono(
+                        string.next(period, brightness, feedback) * env_frame.amplitude,
+                    )
+                });
+                enveloper.progress(state.vars.sample_rate, adsr, sustain, 0.0);
+                voice
+            }
+            // Instrument
+            Node::Instrument(inst) => {
+                let mut enveloper = inst.enveloper.lock();
+                let sustain = state
+                    .resolve_dynamic_value(&inst.sustain_pedal, channel_num, cache)
+                    .unwrap_or(0.0)
+                    != 0.0;
+                enveloper.register(cache.channel_controls(channel_num), sustain, inst.voicing);
+                let adsr = inst
+                    .adsr
+                    .map_or_default(|value| state.resolve_dynamic_value(value, channel_num, cache));
+                let sample_rate = state.vars.sample_rate;
+                let mut output = Voice::SILENT;
+                let mut phases = Vec::new();
+                for env_frame in enveloper.envelopes() {
+                    let freq = inst.tuning.freq(env_frame.pitch);
+                    if freq == 0.0 {
+                        continue;
+                    }
+                    // Every zone that contains the note is layered
+                    let zones: Vec<(usize, &ZoneDef)> = inst
+                        .zones
+                        .iter()
+                        .enumerate()
+                        .filter(|(_, zone)| zone.contains(env_frame.note, env_frame.velocity))
+                        .collect();
+                    // The phase counts the cycles played at the voice's pitch,
+                    // and starts over when a legato note change picks different zones
+                    let source = zones
+                        .iter()
+                        .fold(0xcbf2_9ce4_8422_2325_u64, |hash, (i, _)| {
+                            (hash ^ *i as u64).wrapping_mul(0x0100_0000_01b3)
+                        });
+                    let phase = env_frame.phase(source);
+                    phases.push((
+                        env_frame.id,
+                        source,
+                        phase + f64::from(freq / sample_rate as f32),
+                    ));
+                    for (_, zone) in zones {
+                        if let Some(sample) = state.sample_bank.get(&zone.path).finished() {
+                            if let Ok(sample) = &*sample {
+                                let root_freq = inst.tuning.freq(f32::from(zone.root));
+                                if root_freq == 0.0 {
+                                    continue;
+                                }
+                                let mut pos = phase * f64::from(2_f32.powf(zone.tune / 1200.0))
+                                    / f64::from(root_freq)
+                                    * f64::from(sample.sample_rate());
+                                if let Some((start, end)) = zone.loop_frames {
+                                    let (start, end) = (start as f64, end as f64);
+                                    if end > start && pos >= end {
+                                        pos = start + (pos - start) % (end - start);
+                                    }
+                                }
+                                let voice = sample.voice_at_frame(pos, inst.interpolation);
+                                output +=
+                                    voice * pan_gains(zone.pan) * zone.gain * env_frame.amplitude;
+                            }
+                        }
+                    }
+                }
+                for (id, source, phase) in phases {
+                    enveloper.set_phase(id, source, phase);
+                }
+                let glide = state
+                    .resolve_dynamic_value(&inst.glide, channel_num, cache)
+                    .unwrap_or(0.0);
+                enveloper.progress(state.vars.sample_rate, adsr, sustain, glide);
+                output
+            }
+        }
+    }
+    /// Get the order in which this node processes its channel's controls
+    ///
+    /// Returns `None` if this node does not process controls
+    pub fn control_order(&self) -> Option<u8> {
+        match self {
+            Node::Sequencer(_) => Some(0),
+            Node::NoteProcessor(_) => Some(1),
+            Node::Arp(_) => Some(2),
+            _ => None,
+        }
+    }
+    /// Process the controls of this node's channel
+    pub fn process_controls(&self, channel_num: u8, state: &State, cache: &mut FrameCache) {
+        match self {
+            // Only advance once per frame
+            Node::Sequencer(seq) if !cache.from_loop => seq.tick(channel_num, state, cache),
+            Node::NoteProcessor(notes) => notes.process(channel_num, cache),
+            Node::Arp(arp) => arp.process(channel_num, state, cache),
+            _ => {}
+        }
+    }
+    /// Get the ids of the notes that this node derives from a note
+    pub fn derived_note_ids(&self, id: u64) -> Vec<u64> {
+        match self {
+            Node::NoteProcessor(notes) => (1..notes.chord_len())
+                .map(|i| NoteProcessor::derive_id(id, i))
+                .collect(),
+            _ => Vec::new(),
         }
     }
     pub fn end_envelopes(&mut self, id: u64) {
-        if let Node::Wave(wave) = self {
-            wave.enveloper.lock().end_notes(id);
+        match self {
+            Node::Wave(wave) => wave.enveloper.lock().end_notes(id),
+            Node::Pluck(pluck) => pluck.enveloper.lock().end_notes(id),
+            Node::Instrument(inst) => inst.enveloper.lock().end_notes(id),
+            Node::Arp(arp) => arp
+                .playing
+                .lock()
+                .held
+                .retain(|&(held_id, ..)| held_id != id),
+            Node::NoteProcessor(notes) => {
+                notes.playing.lock().remove(&id);
+            }
+            Node::PitchShift(shift) => {
+                if let Some(harmony) = shift.shifters.lock().harmonies.get_mut(&id) {
+                    harmony.released = true;
+                }
+            }
+            _ => {}
         }
     }
     /// Get a list of this node's input nodes
@@ -496,6 +2088,7 @@ impl Node {
                 .input()
                 .into_iter()
                 .chain(wave.adsr.inputs())
+                .chain(wave.glide.input())
                 .collect(),
             Node::Balance(bal) => once(bal.input.as_str())
                 .chain(bal.volume.input())
@@ -503,11 +2096,129 @@ impl Node {
                 .collect(),
             Node::Filter(filter) => once(filter.input.as_str())
                 .chain(filter.value.input())
+                .chain(filter.resonance.input())
+                .chain(filter.resonance_env.input())
+                .chain(filter.gain.input())
+                .chain(filter.bits.input())
+                .chain(filter.drive.input())
+                .chain(filter.tone.input())
+                .chain(filter.rate.iter().flat_map(DynamicValue::input))
+                .chain(filter.mix.input())
                 .collect(),
             Node::Reverb(reverb) => once(reverb.input.as_str())
                 .chain(reverb.size.input())
-                .chain(reverb.energy_mul.input())
+                .chain(reverb.decay.input())
+                .chain(reverb.damping.input())
+                .chain(reverb.pre_delay.input())
+                .chain(reverb.mix.input())
+                .collect(),
+            Node::Delay(delay) => once(delay.input.as_str())
+                .chain(delay.time.input())
+                .chain(delay.feedback.input())
+                .chain(delay.tone.input())
+                .chain(delay.mix.input())
+                .collect(),
+            Node::PitchShift(shift) => once(shift.input.as_str())
+                .chain(shift.semitones.input())
+                .chain(shift.cents.input())
+                .chain(shift.mix.input())
                 .collect(),
+            Node::Gate(gate) => once(gate.input.as_str())
+                .chain(gate.threshold.input())
+                .chain(gate.attack.input())
+                .chain(gate.hold.input())
+                .chain(gate.release.input())
+                .collect(),
+            Node::Follower(follower) => follower
+                .attack
+                .input()
+                .into_iter()
+                .chain(follower.release.input())
+                .chain(follower.gain.input())
+                .collect(),
+            Node::BusSend(send) => once(send.input.as_str())
+                .chain(send.level.input())
+                .collect(),
+            Node::Mixer(mixer) => mixer
+                .inputs
+                .iter()
+                .flat_map(|input| {
+                    once(input.input.as_str())
+                        .chain(input.gain.input())
+                        .chain(input.pan.input())
+                })
+                .collect(),
+            Node::Equalizer(eq) => once(eq.input.as_str())
+                .chain(eq.bands.iter().flat_map(|band| {
+                    band.freq
+                        .input()
+                        .into_iter()
+                        .chain(band.gain.input())
+                        .chain(band.q.input())
+                }))
+                .collect(),
+            Node::Compressor(comp) => once(comp.input.as_str())
+                .chain(comp.threshold.input())
+                .chain(comp.ratio.input())
+                .chain(comp.attack.input())
+                .chain(comp.release.input())
+                .chain(comp.knee.input())
+                .chain(comp.makeup.input())
+                .collect(),
+            Node::Modulation(modulation) => once(modulation.input.as_str())
+                .chain(modulation.rate.input())
+                .chain(modulation.depth.input())
+                .chain(modulation.feedback.input())
+                .chain(modulation.mix.input())
+                .collect(),
+            Node::Pluck(pluck) => pluck
+                .pitch_bend_range
+                .input()
+                .into_iter()
+                .chain(pluck.brightness.input())
+                .chain(pluck.decay.input())
+                .chain(pluck.adsr.inputs())
+                .collect(),
+            Node::Sampler(sampler) => sampler.adsr.inputs().chain(sampler.glide.input()).collect(),
+            Node::Instrument(inst) => inst.adsr.inputs().chain(inst.glide.input()).collect(),
+            Node::Arp(arp) => arp.gate.input().into_iter().collect(),
+            Node::Sequencer(seq) => seq
+                .swing
+                .input()
+                .into_iter()
+                .chain(seq.gate.input())
+                .collect(),
+            _ => Vec::new(),
+        }
+    }
+    /// Get a list of the nodes whose audio this node processes
+    ///
+    /// Unlike `inputs`, this leaves out nodes that are only read as dynamic values
+    pub fn audio_inputs(&self) -> Vec<&str> {
+        match self {
+            Node::Balance(bal) => vec![bal.input.as_str()],
+            Node::Filter(filter) => vec![filter.input.as_str()],
+            Node::Reverb(reverb) => vec![reverb.input.as_str()],
+            Node::Delay(delay) => vec![delay.input.as_str()],
+            Node::PitchShift(shift) => vec![shift.input.as_str()],
+            Node::Gate(gate) => vec![gate.input.as_str()],
+            Node::BusSend(send) => vec![send.input.as_str()],
+            Node::Mixer(mixer) => mixer
+                .inputs
+                .iter()
+                .map(|input| input.input.as_str())
+                .collect(),
+            Node::Equalizer(eq) => vec![eq.input.as_str()],
+            Node::Compressor(comp) => vec![comp.input.as_str()],
+            Node::Modulation(modulation) => vec![modulation.input.as_str()],
+            _ => Vec::new(),
+        }
+    }
+    /// Get a list of the nodes this node listens to without consuming their output
+    pub fn references(&self) -> Vec<&str> {
+        match self {
+            Node::Compressor(comp) => comp.sidechain.iter().map(Name::as_str).collect(),
+            Node::Follower(follower) => vec![follower.input.as_str()],
             _ => Vec::new(),
         }
     }
@@ -546,6 +2257,21 @@ node_from!(box Wave);
 node_from!(DrumMachine);
 node_from!(box Filter);
 node_from!(Balance);
-node_from!(Reverb);
+node_from!(box Reverb);
 node_from!(box Sampler);
 node_from!(InputPass);
+node_from!(box Pluck);
+node_from!(box Instrument);
+node_from!(box Sequencer);
+node_from!(box Arp);
+node_from!(box NoteProcessor);
+node_from!(box Delay);
+node_from!(box Modulation);
+node_from!(box Compressor);
+node_from!(Equalizer);
+node_from!(Mixer);
+node_from!(BusSend);
+node_from!(BusReturn);
+node_from!(Gate);
+node_from!(Follower);
+node_from!(box PitchShift);
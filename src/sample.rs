@@ -3,13 +3,19 @@ use std::{convert::Infallible, fmt, fs, path::Path, str::FromStr};
 use itertools::Itertools;
 use rodio::{Decoder, Source};
 
-use crate::ty::{Frame, Voice};
+use crate::{
+    spec::Interpolation,
+    ty::{Frame, Voice},
+};
 
 #[derive(Debug, Clone, Copy)]
 pub struct ActiveSampling {
     pub index: usize,
-    pub i: Frame,
+    /// The position in frames of the sample's own sample rate
+    pub pos: f64,
     pub velocity: f32,
+    /// The gain of a choked sampling as it fades out
+    pub choke_fade: Option<f32>,
 }
 
 /// Data for an audio sample
@@ -33,6 +39,13 @@ impl Sample {
     where
         P: AsRef<Path>,
     {
+        if let Some((sf2_path, index)) = crate::sf2::split_sample_path(path.as_ref()) {
+            let (sample_rate, samples) = crate::sf2::load_sample(&sf2_path, index)?;
+            return Ok(Sample {
+                sample_rate,
+                samples,
+            });
+        }
         let path = crate::library::samples_dir()?.join(path);
         let decoder = Decoder::new(fs::File::open(path)?)?;
         let sample_rate = decoder.sample_rate();
@@ -58,9 +71,9 @@ impl Sample {
             samples,
         })
     }
-    pub fn voice(&self, index: Frame, sample_rate: u32) -> Voice {
-        let adjusted = index as usize * self.sample_rate as usize / sample_rate as usize;
-        self.samples[adjusted as usize]
+    /// Get the sample rate of the sample's own frames
+    pub fn sample_rate(&self) -> u32 {
+        self.sample_rate
     }
     pub fn len(&self, sample_rate: u32) -> Frame {
         (u64::from(sample_rate) * self.samples.len() as Frame / Frame::from(self.sample_rate))
@@ -69,10 +82,46 @@ impl Sample {
     pub fn dur_seconds(&self) -> f32 {
         self.samples.len() as f32 / self.sample_rate as f32
     }
-    pub fn voice_at_time(&self, t: f32, sample_rate: u32) -> Voice {
-        let adjusted = t * self.sample_rate as f32 / sample_rate as f32;
-        let i = (adjusted / self.dur_seconds() * self.samples.len() as f32) as usize;
-        self.samples[i]
+    /// Get the voice at a time in seconds
+    pub fn voice_at_time(&self, t: f32, interpolation: Interpolation) -> Voice {
+        self.voice_at_frame(f64::from(t) * f64::from(self.sample_rate), interpolation)
+    }
+    /// Get the voice at a fractional frame of the sample's own sample rate
+    ///
+    /// Frames outside the sample are silent
+    pub fn voice_at_frame(&self, pos: f64, interpolation: Interpolation) -> Voice {
+        let i = pos.floor() as i64;
+        let frac = (pos - pos.floor()) as f32;
+        match interpolation {
+            Interpolation::Nearest => self.get(pos.round() as i64),
+            Interpolation::Linear => self.get(i) * (1.0 - frac) + self.get(i + 1) * frac,
+            Interpolation::Cubic => {
+                // Catmull-Rom spline
+                let (a, b, c, d) = (
+                    self.get(i - 1),
+                    self.get(i),
+                    self.get(i + 1),
+                    self.get(i + 2),
+                );
+                let frac2 = frac * frac;
+                let frac3 = frac2 * frac;
+                (b * 2.0
+                    + (c + a * -1.0) * frac
+                    + (a * 2.0 + b * -5.0 + c * 4.0 + d * -1.0) * frac2
+                    + (a * -1.0 + b * 3.0 + c * -3.0 + d) * frac3)
+                    * 0.5
+            }
+        }
+    }
+    fn get(&self, i: i64) -> Voice {
+        if i < 0 {
+            Voice::SILENT
+        } else {
+            self.samples
+                .get(i as usize)
+                .copied()
+                .unwrap_or(Voice::SILENT)
+        }
     }
 }
 
@@ -0,0 +1,645 @@
+use std::{
+    collections::HashMap,
+    convert::TryInto,
+    ffi::OsString,
+    fs::{self, File},
+    io::{Read, Seek, SeekFrom},
+    path::{Path, PathBuf},
+    sync::{Arc, Mutex},
+    time::SystemTime,
+};
+
+use once_cell::sync::Lazy;
+
+use crate::{
+    library,
+    spec::{ZoneDef, ADSR},
+    ty::Voice,
+};
+
+/// Generator operators
+mod gen {
+    pub const STARTLOOP_ADDRS_OFFSET: u16 = 2;
+    pub const ENDLOOP_ADDRS_OFFSET: u16 = 3;
+    pub const PAN: u16 = 17;
+    pub const ATTACK_VOL_ENV: u16 = 34;
+    pub const DECAY_VOL_ENV: u16 = 36;
+    pub const SUSTAIN_VOL_ENV: u16 = 37;
+    pub const RELEASE_VOL_ENV: u16 = 38;
+    pub const INSTRUMENT: u16 = 41;
+    pub const KEY_RANGE: u16 = 43;
+    pub const VEL_RANGE: u16 = 44;
+    pub const STARTLOOP_ADDRS_COARSE_OFFSET: u16 = 45;
+    pub const INITIAL_ATTENUATION: u16 = 48;
+    pub const ENDLOOP_ADDRS_COARSE_OFFSET: u16 = 50;
+    pub const COARSE_TUNE: u16 = 51;
+    pub const FINE_TUNE: u16 = 52;
+    pub const SAMPLE_ID: u16 = 53;
+    pub const SAMPLE_MODES: u16 = 54;
+    pub const OVERRIDING_ROOT_KEY: u16 = 58;
+}
+
+/// The default value of the volume envelope timecent generators
+const DEFAULT_TIMECENTS: i16 = -12000;
+/// Samples of this type are stored in ROM and cannot be played
+const ROM_SAMPLE: u16 = 0x8000;
+
+fn sf2_error(path: &Path, message: impl Into<String>) -> crate::Error {
+    crate::Error::Instrument(path.into(), message.into())
+}
+
+/// Get the sample bank path of a sample in a SoundFont
+pub fn sample_path(path: &Path, index: usize) -> PathBuf {
+    let mut path = OsString::from(path);
+    path.push(format!("#{}", index));
+    path.into()
+}
+
+/// Split a sample bank path into the path of a SoundFont and a sample index
+pub fn split_sample_path(path: &Path) -> Option<(PathBuf, usize)> {
+    let path = path.to_str()?;
+    let split = path.rfind('#')?;
+    let (file, index) = (&path[..split], &path[split + 1..]);
+    if file.to_lowercase().ends_with(".sf2") {
+        Some((file.into(), index.parse().ok()?))
+    } else {
+        None
+    }
+}
+
+fn u16_at(bytes: &[u8], i: usize) -> u16 {
+    u16::from_le_bytes(bytes[i..i + 2].try_into().unwrap())
+}
+
+fn u32_at(bytes: &[u8], i: usize) -> u32 {
+    u32::from_le_bytes(bytes[i..i + 4].try_into().unwrap())
+}
+
+/// A generator amount
+#[derive(Debug, Clone, Copy)]
+struct Amount([u8; 2]);
+
+impl Amount {
+    fn signed(self) -> i16 {
+        i16::from_le_bytes(self.0)
+    }
+    fn unsigned(self) -> u16 {
+        u16::from_le_bytes(self.0)
+    }
+    fn range(self) -> (u8, u8) {
+        (self.0[0], self.0[1])
+    }
+}
+
+type Generators = HashMap<u16, Amount>;
+
+#[derive(Debug, Clone, Copy)]
+struct SampleHeader {
+    start: u32,
+    end: u32,
+    loop_start: u32,
+    loop_end: u32,
+    sample_rate: u32,
+    original_pitch: u8,
+    pitch_correction: i8,
+    ty: u16,
+}
+
+/// The preset, instrument, and sample data of a SoundFont
+#[derive(Debug, Default)]
+struct Hydra {
+    /// Preset number, bank, and first bag index
+    presets: Vec<(u16, u16, usize)>,
+    preset_bags: Vec<usize>,
+    preset_gens: Vec<(u16, Amount)>,
+    /// First bag index
+    instruments: Vec<usize>,
+    instrument_bags: Vec<usize>,
+    instrument_gens: Vec<(u16, Amount)>,
+    samples: Vec<SampleHeader>,
+}
+
+impl Hydra {
+    fn parse(pdta: &[u8]) -> Self {
+        let mut hydra = Hydra::default();
+        for (id, data) in subchunks(pdta) {
+            match &id {
+                b"phdr" => {
+                    hydra.presets = records(data, 38)
+                        .map(|r| (u16_at(r, 20), u16_at(r, 22), usize::from(u16_at(r, 24))))
+                        .collect()
+                }
+                b"pbag" => {
+                    hydra.preset_bags = records(data, 4)
+                        .map(|r| usize::from(u16_at(r, 0)))
+                        .collect()
+                }
+                b"pgen" => hydra.preset_gens = records(data, 4).map(generator).collect(),
+                b"inst" => {
+                    hydra.instruments = records(data, 22)
+                        .map(|r| usize::from(u16_at(r, 20)))
+                        .collect()
+                }
+                b"ibag" => {
+                    hydra.instrument_bags = records(data, 4)
+                        .map(|r| usize::from(u16_at(r, 0)))
+                        .collect()
+                }
+                b"igen" => hydra.instrument_gens = records(data, 4).map(generator).collect(),
+                b"shdr" => {
+                    hydra.samples = records(data, 46)
+                        .map(|r| SampleHeader {
+                            start: u32_at(r, 20),
+                            end: u32_at(r, 24),
+                            loop_start: u32_at(r, 28),
+                            loop_end: u32_at(r, 32),
+                            sample_rate: u32_at(r, 36),
+                            original_pitch: r[40],
+                            pitch_correction: r[41] as i8,
+                            ty: u16_at(r, 44),
+                        })
+                        .collect()
+                }
+                _ => {}
+            }
+        }
+        hydra
+    }
+    /// Get the generators of each zone in a range of bags
+    ///
+    /// The first zone is global if it does not end with the terminal generator.
+    /// The global zone's generators are merged into the other zones.
+    fn zones(
+        bags: &[usize],
+        gens: &[(u16, Amount)],
+        first_bag: usize,
+        end_bag: usize,
+        terminal: u16,
+    ) -> Vec<Generators> {
+        let mut global = Generators::new();
+        let mut zones = Vec::new();
+        for bag in first_bag..end_bag {
+            let (start, end) = match (bags.get(bag), bags.get(bag + 1)) {
+                (Some(&start), Some(&end)) if start <= end && end <= gens.len() => (start, end),
+                _ => break,
+            };
+            let zone_gens = &gens[start..end];
+            if zone_gens.last().map(|(oper, _)| *oper) == Some(terminal) {
+                let mut zone = global.clone();
+                zone.extend(zone_gens.iter().copied());
+                zones.push(zone);
+            } else if bag == first_bag {
+                global = zone_gens.iter().copied().collect();
+            }
+        }
+        zones
+    }
+}
+
+fn generator(record: &[u8]) -> (u16, Amount) {
+    (u16_at(record, 0), Amount([record[2], record[3]]))
+}
+
+fn records(data: &[u8], size: usize) -> impl Iterator<Item = &[u8]> {
+    data.chunks_exact(size)
+}
+
+/// Iterate over the subchunks of a list chunk body
+fn subchunks(mut data: &[u8]) -> impl Iterator<Item = ([u8; 4], &[u8])> {
+    std::iter::from_fn(move || {
+        if data.len() < 8 {
+            return None;
+        }
+        let id = data[0..4].try_into().unwrap();
+        let size = (u32_at(data, 4) as usize).min(data.len() - 8);
+        let chunk = &data[8..8 + size];
+        data = &data[(8 + size + (size & 1)).min(data.len())..];
+        Some((id, chunk))
+    })
+}
+
+/// The location of the sample data and the contents of the preset data
+struct Layout {
+    /// The file offset and length in bytes of the sample data
+    smpl: Option<(u64, u64)>,
+    pdta: Vec<u8>,
+}
+
+fn read_chunk_header(file: &mut File) -> std::io::Result<([u8; 4], u64)> {
+    let mut header = [0; 8];
+    file.read_exact(&mut header)?;
+    Ok((
+        header[0..4].try_into().unwrap(),
+        u64::from(u32_at(&header, 4)),
+    ))
+}
+
+/// Find the sample data and read the preset data without reading the samples
+fn read_layout(file: &mut File, path: &Path) -> crate::Result<Layout> {
+    let (riff, riff_size) = read_chunk_header(file)?;
+    let mut form = [0; 4];
+    file.read_exact(&mut form)?;
+    if &riff != b"RIFF" || &form != b"sfbk" {
+        return Err(sf2_error(path, "Not a SoundFont 2 file"));
+    }
+    let file_len = file.metadata()?.len();
+    let mut layout = Layout {
+        smpl: None,
+        pdta: Vec::new(),
+    };
+    let riff_end = 8 + riff_size;
+    let mut pos = 12;
+    while pos + 12 <= riff_end {
+        file.seek(SeekFrom::Start(pos))?;
+        let (id, size) = read_chunk_header(file)?;
+        if &id == b"LIST" {
+            let mut list_type = [0; 4];
+            file.read_exact(&mut list_type)?;
+            match &list_type {
+                b"sdta" => {
+                    let list_end = pos + 8 + size;
+                    let mut sub_pos = pos + 12;
+                    while sub_pos + 8 <= list_end {
+                        file.seek(SeekFrom::Start(sub_pos))?;
+                        let (sub_id, sub_size) = read_chunk_header(file)?;
+                        if &sub_id == b"smpl" {
+                            layout.smpl = Some((sub_pos + 8, sub_size));
+                        }
+                        sub_pos += 8 + sub_size + (sub_size & 1);
+                    }
+                }
+                b"pdta" => {
+                    if pos + 8 + size > file_len {
+                        return Err(sf2_error(
+                            path,
+                            "Preset data extends past the end of the file",
+                        ));
+                    }
+                    layout.pdta = vec![0; size.saturating_sub(4) as usize];
+                    file.read_exact(&mut layout.pdta)?;
+                }
+                _ => {}
+            }
+        }
+        pos += 8 + size + (size & 1);
+    }
+    Ok(layout)
+}
+
+/// An open SoundFont with its preset data parsed
+struct SoundFont {
+    file: File,
+    layout: Layout,
+    hydra: Hydra,
+    /// The modification time of the file when it was opened
+    modified: Option<SystemTime>,
+}
+
+/// Open SoundFonts, so that a preset's samples can be loaded without parsing the file for each one
+static SOUND_FONTS: Lazy<Mutex<HashMap<PathBuf, Arc<Mutex<SoundFont>>>>> =
+    Lazy::new(|| Mutex::new(HashMap::new()));
+
+/// Open a SoundFont, reusing it if it was already opened and has not changed
+fn open(path: &Path) -> crate::Result<Arc<Mutex<SoundFont>>> {
+    let full_path = library::samples_dir()?.join(path);
+    let modified = fs::metadata(&full_path)?.modified().ok();
+    let mut sound_fonts = SOUND_FONTS.lock().unwrap();
+    if let Some(sound_font) = sound_fonts.get(path) {
+        if sound_font.lock().unwrap().modified == modified {
+            return Ok(Arc::clone(sound_font));
+        }
+    }
+    let mut file = File::open(full_path)?;
+    let layout = read_layout(&mut file, path)?;
+    let hydra = Hydra::parse(&layout.pdta);
+    let sound_font = Arc::new(Mutex::new(SoundFont {
+        file,
+        layout,
+        hydra,
+        modified,
+    }));
+    sound_fonts.insert(path.into(), Arc::clone(&sound_font));
+    Ok(sound_font)
+}
+
+fn timecents_to_seconds(timecents: i16) -> f32 {
+    2_f32.powf(f32::from(timecents) / 1200.0)
+}
+
+fn centibels_to_gain(centibels: i16) -> f32 {
+    10_f32.powf(-f32::from(centibels.max(0)) / 200.0)
+}
+
+/// Intersect two key or velocity ranges
+fn intersect(a: (u8, u8), b: (u8, u8)) -> Option<(u8, u8)> {
+    let range = (a.0.max(b.0), a.1.min(b.1));
+    if range.0 <= range.1 {
+        Some(range)
+    } else {
+        None
+    }
+}
+
+/// Load the zones of a preset from a SoundFont
+///
+/// Also returns the volume envelope of the preset's first zone
+pub fn load_zones(
+    path: &Path,
+    bank: u16,
+    preset: u16,
+) -> crate::Result<(Vec<ZoneDef>, Option<ADSR<f32>>)> {
+    let sound_font = open(path)?;
+    let hydra = &sound_font.lock().unwrap().hydra;
+    preset_zones(hydra, path, bank, preset)
+}
+
+/// Get the zones of a preset from the preset data of the SoundFont at the given path
+fn preset_zones(
+    hydra: &Hydra,
+    path: &Path,
+    bank: u16,
+    preset: u16,
+) -> crate::Result<(Vec<ZoneDef>, Option<ADSR<f32>>)> {
+    // The last preset header is a terminal record
+    let index = hydra
+        .presets
+        .iter()
+        .take(hydra.presets.len().saturating_sub(1))
+        .position(|&(p, b, _)| p == preset && b == bank)
+        .ok_or_else(|| sf2_error(path, format!("No preset {} in bank {}", preset, bank)))?;
+    let preset_zones = Hydra::zones(
+        &hydra.preset_bags,
+        &hydra.preset_gens,
+        hydra.presets[index].2,
+        hydra.presets[index + 1].2,
+        gen::INSTRUMENT,
+    );
+    let mut zones = Vec::new();
+    let mut envelope = None;
+    for preset_gens in preset_zones {
+        let instrument = usize::from(preset_gens[&gen::INSTRUMENT].unsigned());
+        let (first_bag, end_bag) = match (
+            hydra.instruments.get(instrument),
+            hydra.instruments.get(instrument + 1),
+        ) {
+            (Some(&first), Some(&end)) => (first, end),
+            _ => return Err(sf2_error(path, "Invalid instrument index")),
+        };
+        let instrument_zones = Hydra::zones(
+            &hydra.instrument_bags,
+            &hydra.instrument_gens,
+            first_bag,
+            end_bag,
+            gen::SAMPLE_ID,
+        );
+        for inst_gens in instrument_zones {
+            let sample_index = usize::from(inst_gens[&gen::SAMPLE_ID].unsigned());
+            let sample = if let Some(sample) = hydra.samples.get(sample_index) {
+                sample
+            } else {
+                return Err(sf2_error(path, "Invalid sample index"));
+            };
+            if sample.ty & ROM_SAMPLE != 0 {
+                continue;
+            }
+            let range = |oper| {
+                let get = |gens: &Generators| gens.get(&oper).map_or((0, 127), |a| a.range());
+                intersect(get(&inst_gens), get(&preset_gens))
+            };
+            // Preset generators are added to instrument generators
+            let sum = |oper, default: i16| {
+                inst_gens.get(&oper).map_or(default, |a| a.signed())
+                    + preset_gens.get(&oper).map_or(0, |a| a.signed())
+            };
+            let (keys, velocities) = match (range(gen::KEY_RANGE), range(gen::VEL_RANGE)) {
+                (Some(keys), Some(velocities)) => (keys, velocities),
+                _ => continue,
+            };
+            let root = match inst_gens.get(&gen::OVERRIDING_ROOT_KEY).map(|a| a.signed()) {
+                Some(root) if root >= 0 => root.min(127) as u8,
+                _ if sample.original_pitch <= 127 => sample.original_pitch,
+                _ => 60,
+            };
+            let tune = f32::from(sum(gen::COARSE_TUNE, 0)) * 100.0
+                + f32::from(sum(gen::FINE_TUNE, 0))
+                + f32::from(sample.pitch_correction);
+            let sample_mode = inst_gens
+                .get(&gen::SAMPLE_MODES)
+                .map_or(0, |a| a.unsigned());
+            let loop_frames = if sample_mode & 1 == 1 {
+                let offset = |fine, coarse| {
+                    i64::from(inst_gens.get(&fine).map_or(0, |a| a.signed()))
+                        + 32768 * i64::from(inst_gens.get(&coarse).map_or(0, |a| a.signed()))
+                };
+                let start = i64::from(sample.loop_start) - i64::from(sample.start)
+                    + offset(
+                        gen::STARTLOOP_ADDRS_OFFSET,
+                        gen::STARTLOOP_ADDRS_COARSE_OFFSET,
+                    );
+                let end = i64::from(sample.loop_end) - i64::from(sample.start)
+                    + offset(gen::ENDLOOP_ADDRS_OFFSET, gen::ENDLOOP_ADDRS_COARSE_OFFSET);
+                Some((start.max(0) as u64, end.max(0) as u64))
+            } else {
+                None
+            };
+            envelope.get_or_insert_with(|| ADSR {
+                attack: timecents_to_seconds(sum(gen::ATTACK_VOL_ENV, DEFAULT_TIMECENTS)),
+                decay: timecents_to_seconds(sum(gen::DECAY_VOL_ENV, DEFAULT_TIMECENTS)),
+                sustain: centibels_to_gain(sum(gen::SUSTAIN_VOL_ENV, 0)),
+                release: timecents_to_seconds(sum(gen::RELEASE_VOL_ENV, DEFAULT_TIMECENTS)),
+            });
+            zones.push(ZoneDef {
+                path: sample_path(path, sample_index),
+                keys,
+                velocities,
+                root,
+                tune,
+                loop_frames,
+                gain: centibels_to_gain(sum(gen::INITIAL_ATTENUATION, 0)),
+                // SoundFont pans from -500 (left) to 500 (right)
+                pan: (-f32::from(sum(gen::PAN, 0)) / 500.0).max(-1.0).min(1.0),
+            });
+        }
+    }
+    Ok((zones, envelope))
+}
+
+/// Load a single sample from a SoundFont
+///
+/// Returns the sample rate and the sample data
+pub fn load_sample(path: &Path, index: usize) -> crate::Result<(u32, Vec<Voice>)> {
+    let sound_font = open(path)?;
+    let SoundFont {
+        file,
+        layout,
+        hydra,
+        ..
+    } = &mut *sound_font.lock().unwrap();
+    let sample = hydra
+        .samples
+        .get(index)
+        .ok_or_else(|| sf2_error(path, format!("No sample {}", index)))?;
+    let (offset, len) = layout
+        .smpl
+        .ok_or_else(|| sf2_error(path, "No sample data"))?;
+    let start = u64::from(sample.start) * 2;
+    let end = (u64::from(sample.end) * 2).min(len);
+    if start >= end {
+        return Ok((sample.sample_rate.max(1), Vec::new()));
+    }
+    file.seek(SeekFrom::Start(offset + start))?;
+    let mut bytes = vec![0; (end - start) as usize];
+    file.read_exact(&mut bytes)?;
+    let samples = bytes
+        .chunks_exact(2)
+        .map(|b| Voice::mono(f32::from(i16::from_le_bytes([b[0], b[1]])) / f32::from(i16::MAX)))
+        .collect();
+    Ok((sample.sample_rate.max(1), samples))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn chunk(id: &[u8; 4], data: Vec<u8>) -> Vec<u8> {
+        let mut chunk = id.to_vec();
+        chunk.extend(&(data.len() as u32).to_le_bytes());
+        chunk.extend(data);
+        chunk
+    }
+
+    fn record(size: usize, fields: &[(usize, &[u8])]) -> Vec<u8> {
+        let mut record = vec![0; size];
+        for (i, bytes) in fields {
+            record[*i..*i + bytes.len()].copy_from_slice(bytes);
+        }
+        record
+    }
+
+    fn gens(gens: &[(u16, [u8; 2])]) -> Vec<u8> {
+        gens.iter()
+            .chain(Some(&(0, [0, 0])))
+            .flat_map(|(oper, amount)| {
+                oper.to_le_bytes()
+                    .iter()
+                    .chain(amount)
+                    .copied()
+                    .collect::<Vec<_>>()
+            })
+            .collect()
+    }
+
+    fn bags(bags: &[u16]) -> Vec<u8> {
+        bags.iter()
+            .flat_map(|bag| record(4, &[(0, &bag.to_le_bytes())]))
+            .collect()
+    }
+
+    /// Build the preset data of a SoundFont with one preset, one instrument, and one sample
+    fn pdta() -> Vec<u8> {
+        let phdr = [0u16, 1]
+            .iter()
+            .flat_map(|&bag| record(38, &[(24, &bag.to_le_bytes())]))
+            .collect();
+        let pgen = gens(&[(gen::KEY_RANGE, [48, 72]), (gen::INSTRUMENT, [0, 0])]);
+        let inst = [0u16, 3]
+            .iter()
+            .flat_map(|&bag| record(22, &[(20, &bag.to_le_bytes())]))
+            .collect();
+        let igen = gens(&[
+            // Global zone
+            (gen::ATTACK_VOL_ENV, (-1200_i16).to_le_bytes()),
+            // Zone that overlaps the preset's key range
+            (gen::KEY_RANGE, [60, 84]),
+            (gen::PAN, 250_i16.to_le_bytes()),
+            (gen::SAMPLE_MODES, [1, 0]),
+            (gen::SAMPLE_ID, [0, 0]),
+            // Zone outside of the preset's key range
+            (gen::KEY_RANGE, [0, 40]),
+            (gen::SAMPLE_ID, [0, 0]),
+        ]);
+        let shdr = record(
+            46,
+            &[
+                (20, &100u32.to_le_bytes()),
+                (24, &1100u32.to_le_bytes()),
+                (28, &200u32.to_le_bytes()),
+                (32, &900u32.to_le_bytes()),
+                (36, &22050u32.to_le_bytes()),
+                (40, &[57, (-10_i8) as u8]),
+                (44, &1u16.to_le_bytes()),
+            ],
+        )
+        .into_iter()
+        .chain(vec![0; 46])
+        .collect();
+        [
+            chunk(b"phdr", phdr),
+            chunk(b"pbag", bags(&[0, 2])),
+            chunk(b"pgen", pgen),
+            chunk(b"inst", inst),
+            chunk(b"ibag", bags(&[0, 1, 5, 7])),
+            chunk(b"igen", igen),
+            chunk(b"shdr", shdr),
+        ]
+        .concat()
+    }
+
+    #[test]
+    fn zones() {
+        let path = Path::new("test.sf2");
+        let hydra = Hydra::parse(&pdta());
+        let (zones, envelope) = preset_zones(&hydra, path, 0, 0).unwrap();
+        assert_eq!(
+            zones,
+            vec![ZoneDef {
+                path: sample_path(path, 0),
+                keys: (60, 72),
+                velocities: (0, 127),
+                root: 57,
+                tune: -10.0,
+                loop_frames: Some((100, 800)),
+                gain: 1.0,
+                pan: -0.5,
+            }]
+        );
+        let envelope = envelope.unwrap();
+        assert!((envelope.attack - 0.5).abs() < 0.001);
+        assert!((envelope.sustain - 1.0).abs() < 0.001);
+        assert!(preset_zones(&hydra, path, 0, 1).is_err());
+    }
+
+    #[test]
+    fn layout() {
+        let path = std::env::temp_dir().join("ryvm-test-layout.sf2");
+        let list = |ty: &[u8; 4], chunks: Vec<u8>| chunk(b"LIST", [&ty[..], &chunks].concat());
+        let body = [
+            &b"sfbk"[..],
+            &list(b"INFO", chunk(b"ifil", vec![2, 0, 1, 0])),
+            &list(b"sdta", chunk(b"smpl", vec![0; 2400])),
+            &list(b"pdta", pdta()),
+        ]
+        .concat();
+        fs::write(&path, chunk(b"RIFF", body.clone())).unwrap();
+        let layout = read_layout(&mut File::open(&path).unwrap(), &path).unwrap();
+        // The sample data follows the RIFF header, the INFO list, and the sdta and smpl headers
+        assert_eq!(layout.smpl, Some((12 + 24 + 12 + 8, 2400)));
+        assert_eq!(layout.pdta, pdta());
+        // Truncated preset data is an error
+        fs::write(&path, chunk(b"RIFF", body[..body.len() - 10].to_vec())).unwrap();
+        assert!(read_layout(&mut File::open(&path).unwrap(), &path).is_err());
+        fs::write(&path, b"RIFF\0\0\0\0WAVE").unwrap();
+        assert!(read_layout(&mut File::open(&path).unwrap(), &path).is_err());
+        fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn sample_paths() {
+        let path = sample_path(Path::new("fonts/piano.sf2"), 3);
+        assert_eq!(
+            split_sample_path(&path),
+            Some((PathBuf::from("fonts/piano.sf2"), 3))
+        );
+        assert_eq!(split_sample_path(Path::new("drums/kick.wav")), None);
+    }
+}
@@ -0,0 +1,283 @@
+use std::{collections::HashMap, fs, path::Path};
+
+use crate::{library, spec::ZoneDef};
+
+type Opcodes = HashMap<String, String>;
+
+/// The header levels whose opcodes apply to the regions beneath them
+#[derive(Default)]
+struct Levels {
+    control: Opcodes,
+    global: Opcodes,
+    master: Opcodes,
+    group: Opcodes,
+    region: Option<Opcodes>,
+}
+
+impl Levels {
+    /// Get the value of an opcode for the current region
+    fn get(&self, opcode: &str) -> Option<&str> {
+        self.region
+            .iter()
+            .chain(Some(&self.group))
+            .chain(Some(&self.master))
+            .chain(Some(&self.global))
+            .find_map(|opcodes| opcodes.get(opcode))
+            .map(String::as_str)
+    }
+    fn current(&mut self, header: &str) -> &mut Opcodes {
+        match header {
+            "region" => self.region.get_or_insert_with(Opcodes::new),
+            "group" => &mut self.group,
+            "master" => &mut self.master,
+            "global" => &mut self.global,
+            _ => &mut self.control,
+        }
+    }
+}
+
+fn sfz_error(path: &Path, message: impl Into<String>) -> crate::Error {
+    crate::Error::Instrument(path.into(), message.into())
+}
+
+/// Load the zones from an SFZ file
+///
+/// The path is relative to the ryvm samples directory, as are the paths of the returned zones.
+pub fn load(path: &Path) -> crate::Result<Vec<ZoneDef>> {
+    let text = fs::read_to_string(library::samples_dir()?.join(path))?;
+    parse(path, &text)
+}
+
+/// Parse the zones from the text of an SFZ file at the given path
+fn parse(path: &Path, text: &str) -> crate::Result<Vec<ZoneDef>> {
+    let folder = path.parent().unwrap_or_else(|| Path::new(""));
+    let mut levels = Levels::default();
+    let mut header = String::new();
+    let mut zones = Vec::new();
+    let tokens: Vec<&str> = text
+        .lines()
+        .map(|line| line.find("//").map_or(line, |i| &line[..i]))
+        .flat_map(str::split_whitespace)
+        .flat_map(split_headers)
+        .collect();
+    let mut i = 0;
+    while i < tokens.len() {
+        let token = tokens[i];
+        i += 1;
+        if token.starts_with('<') && token.ends_with('>') {
+            // Finish the previous region
+            if let Some(zone) = region_zone(path, folder, &levels)? {
+                zones.push(zone);
+            }
+            levels.region = None;
+            header = token[1..token.len() - 1].to_string();
+            match header.as_str() {
+                "global" => {
+                    levels.global.clear();
+                    levels.master.clear();
+                    levels.group.clear();
+                }
+                "master" => {
+                    levels.master.clear();
+                    levels.group.clear();
+                }
+                "group" => levels.group.clear(),
+                "region" => levels.region = Some(Opcodes::new()),
+                _ => {}
+            }
+        } else if let Some(eq) = token.find('=') {
+            let opcode = token[..eq].to_string();
+            let mut value = token[eq + 1..].to_string();
+            // Sample paths may contain spaces
+            while i < tokens.len() && !tokens[i].contains('=') && !tokens[i].starts_with('<') {
+                value.push(' ');
+                value.push_str(tokens[i]);
+                i += 1;
+            }
+            let opcodes = levels.current(&header);
+            // A key sets the key range and root at the same level
+            if opcode == "key" {
+                for opcode in &["lokey", "hikey", "pitch_keycenter"] {
+                    opcodes.insert(opcode.to_string(), value.clone());
+                }
+            }
+            opcodes.insert(opcode, value);
+        } else {
+            return Err(sfz_error(path, format!("Unexpected token {:?}", token)));
+        }
+    }
+    if let Some(zone) = region_zone(path, folder, &levels)? {
+        zones.push(zone);
+    }
+    Ok(zones)
+}
+
+/// Split the headers out of a token
+///
+/// Headers do not have to be separated from opcodes by whitespace, as in `<region>sample=kick.wav`
+fn split_headers(mut token: &str) -> Vec<&str> {
+    let mut parts = Vec::new();
+    while !token.is_empty() {
+        let end = if token.starts_with('<') {
+            token.find('>').map_or(token.len(), |i| i + 1)
+        } else {
+            token.find('<').unwrap_or(token.len())
+        };
+        parts.push(&token[..end]);
+        token = &token[end..];
+    }
+    parts
+}
+
+/// Create a zone from the current region
+fn region_zone(path: &Path, folder: &Path, levels: &Levels) -> crate::Result<Option<ZoneDef>> {
+    if levels.region.is_none() {
+        return Ok(None);
+    }
+    let sample = if let Some(sample) = levels.get("sample") {
+        sample.replace('\\', "/")
+    } else {
+        return Ok(None);
+    };
+    let default_path = levels
+        .control
+        .get("default_path")
+        .map_or("", String::as_str);
+    let note = |opcode: &str| {
+        levels
+            .get(opcode)
+            .map(|value| parse_note(value).ok_or_else(|| invalid(path, opcode, value)))
+            .transpose()
+    };
+    let number = |opcode: &str| {
+        levels
+            .get(opcode)
+            .map(|value| {
+                value
+                    .parse::<f32>()
+                    .map_err(|_| invalid(path, opcode, value))
+            })
+            .transpose()
+    };
+    let keys = (note("lokey")?.unwrap_or(0), note("hikey")?.unwrap_or(127));
+    let velocities = (
+        number("lovel")?.map_or(0, |v| v as u8),
+        number("hivel")?.map_or(127, |v| v as u8),
+    );
+    let transpose = number("transpose")?.unwrap_or(0.0) as i16;
+    let root = note("pitch_keycenter")?.unwrap_or(60);
+    let root = (i16::from(root) - transpose).max(0).min(127) as u8;
+    let loop_start = number("loop_start")?.or(number("loopstart")?);
+    let loop_end = number("loop_end")?.or(number("loopend")?);
+    let looping = match levels.get("loop_mode").or_else(|| levels.get("loopmode")) {
+        Some("loop_continuous") | Some("loop_sustain") => true,
+        Some(_) => false,
+        None => loop_end.is_some(),
+    };
+    let loop_frames = if looping {
+        loop_end.map(|end| (loop_start.unwrap_or(0.0) as u64, end as u64))
+    } else {
+        None
+    };
+    let tune = number("tune")?.unwrap_or(0.0);
+    let gain = 10_f32.powf(number("volume")?.unwrap_or(0.0) / 20.0);
+    // SFZ pans from -100 (left) to 100 (right)
+    let pan = (-number("pan")?.unwrap_or(0.0) / 100.0).max(-1.0).min(1.0);
+    Ok(Some(ZoneDef {
+        path: folder.join(default_path.replace('\\', "/")).join(sample),
+        keys,
+        velocities,
+        root,
+        tune,
+        loop_frames,
+        gain,
+        pan,
+    }))
+}
+
+fn invalid(path: &Path, opcode: &str, value: &str) -> crate::Error {
+    sfz_error(path, format!("Invalid {} value {:?}", opcode, value))
+}
+
+/// Parse a note number or name like `c#4`, where `c4` is note 60
+fn parse_note(s: &str) -> Option<u8> {
+    if let Ok(n) = s.parse::<u8>() {
+        return Some(n);
+    }
+    let s = s.to_lowercase();
+    let mut chars = s.chars();
+    let mut semitone: i16 = match chars.next()? {
+        'c' => 0,
+        'd' => 2,
+        'e' => 4,
+        'f' => 5,
+        'g' => 7,
+        'a' => 9,
+        'b' => 11,
+        _ => return None,
+    };
+    let mut rest = chars.as_str();
+    if let Some(stripped) = rest.strip_prefix('#') {
+        semitone += 1;
+        rest = stripped;
+    } else if let Some(stripped) = rest.strip_prefix('b') {
+        semitone -= 1;
+        rest = stripped;
+    }
+    let octave: i16 = rest.parse().ok()?;
+    let note = (octave + 1) * 12 + semitone;
+    if (0..=127).contains(&note) {
+        Some(note as u8)
+    } else {
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn regions() {
+        let text = "\
+<control> default_path=samples\\
+<group> lokey=c4 hikey=b4 volume=-6 // The group's opcodes apply to its regions
+<region> sample=piano c4.wav pitch_keycenter=c4
+<region>sample=piano f#4.wav pitch_keycenter=f#4 hivel=100 pan=-100
+<region>sample=piano a4.wav key=69 loop_start=10 loop_end=100 tune=-20
+";
+        let zones = parse(Path::new("piano/piano.sfz"), text).unwrap();
+        assert_eq!(zones.len(), 3);
+        assert_eq!(
+            zones[0].path,
+            Path::new("piano/samples/piano c4.wav").to_path_buf()
+        );
+        assert_eq!(zones[0].keys, (60, 71));
+        assert_eq!(zones[0].root, 60);
+        assert!((zones[0].gain - 0.501).abs() < 0.001);
+        assert_eq!(zones[1].root, 66);
+        assert_eq!(zones[1].velocities, (0, 100));
+        assert!((zones[1].pan - 1.0).abs() < f32::EPSILON);
+        assert_eq!(zones[2].keys, (69, 69));
+        assert_eq!(zones[2].root, 69);
+        assert_eq!(zones[2].loop_frames, Some((10, 100)));
+        assert!((zones[2].tune + 20.0).abs() < f32::EPSILON);
+    }
+
+    #[test]
+    fn unexpected_token() {
+        assert!(parse(Path::new("bad.sfz"), "<region> sample=a.wav <region").is_err());
+        assert!(parse(Path::new("bad.sfz"), "<region> lokey=x sample=a.wav").is_err());
+    }
+
+    #[test]
+    fn note_names() {
+        assert_eq!(parse_note("c4"), Some(60));
+        assert_eq!(parse_note("C#4"), Some(61));
+        assert_eq!(parse_note("eb3"), Some(51));
+        assert_eq!(parse_note("a-1"), Some(9));
+        assert_eq!(parse_note("64"), Some(64));
+        assert_eq!(parse_note("g9"), Some(127));
+        assert_eq!(parse_note("a9"), None);
+    }
+}
@@ -1,5 +1,8 @@
 //! All the Ryvm spec default values
-use super::{DynamicValue, FilterType, ADSR};
+use super::{
+    ArpMode, DistortionCurve, DynamicValue, EqShape, FilterType, Interpolation, ModulationMode,
+    PitchShiftMode, Scale, ScaleDef, VoiceMode, VoiceSteal, ADSR,
+};
 
 macro_rules! default {
     (#[$attr:meta] const $constant:ident: $type:ty = $val:expr; $def_fn_name:ident; $is_def_fn_name:ident;) => {
@@ -93,10 +96,31 @@ default! {
 }
 
 default! {
-    /// The default reverb room size
-    const ENERGY_MUL: DynamicValue = DynamicValue::Static(0.5);
-    energy_mul;
-    is_energy_mul;
+    /// The default reverb decay
+    const REVERB_DECAY: DynamicValue = DynamicValue::Static(0.5);
+    reverb_decay;
+    is_reverb_decay;
+}
+
+default! {
+    /// The default reverb damping
+    const DAMPING: DynamicValue = DynamicValue::Static(0.5);
+    damping;
+    is_damping;
+}
+
+default! {
+    /// The default reverb pre-delay
+    const PRE_DELAY: DynamicValue = DynamicValue::Static(0.0);
+    pre_delay;
+    is_pre_delay;
+}
+
+default! {
+    /// The default wet/dry mix
+    const MIX: DynamicValue = DynamicValue::Static(0.5);
+    mix;
+    is_mix;
 }
 
 default! {
@@ -117,3 +141,430 @@ default! {
     sustain_pedal;
     is_sustain_pedal;
 }
+
+default! {
+    /// The default voice stealing policy
+    const VOICE_STEAL: VoiceSteal = VoiceSteal::Oldest;
+    voice_steal;
+    is_voice_steal;
+}
+
+default! {
+    /// The default voice mode
+    const VOICE_MODE: VoiceMode = VoiceMode::Poly;
+    voice_mode;
+    is_voice_mode;
+}
+
+default! {
+    /// The default glide time
+    const GLIDE: DynamicValue = DynamicValue::Static(0.0);
+    glide;
+    is_glide;
+}
+
+default! {
+    /// The default pluck brightness
+    const BRIGHTNESS: DynamicValue = DynamicValue::Static(0.5);
+    brightness;
+    is_brightness;
+}
+
+default! {
+    /// The default pluck decay time
+    const PLUCK_DECAY: DynamicValue = DynamicValue::Static(2.0);
+    pluck_decay;
+    is_pluck_decay;
+}
+
+default! {
+    /// The default sample interpolation
+    const INTERPOLATION: Interpolation = Interpolation::Linear;
+    interpolation;
+    is_interpolation;
+}
+
+default! {
+    /// The default zone key and velocity range
+    const ZONE_RANGE: (u8, u8) = (0, 127);
+    zone_range;
+    is_zone_range;
+}
+
+default! {
+    /// The default zone root note
+    const ROOT: u8 = 60;
+    root;
+    is_root;
+}
+
+default! {
+    /// The default zone tuning in cents
+    const ZONE_TUNE: f32 = 0.0;
+    zone_tune;
+    is_zone_tune;
+}
+
+default! {
+    /// The default zone gain
+    const GAIN: f32 = 1.0;
+    gain;
+    is_gain;
+}
+
+default! {
+    /// The default zone pan
+    const ZONE_PAN: f32 = 0.0;
+    zone_pan;
+    is_zone_pan;
+}
+
+default! {
+    /// The default SoundFont bank
+    const SF2_BANK: u16 = 0;
+    sf2_bank;
+    is_sf2_bank;
+}
+
+default! {
+    /// The default SoundFont preset
+    const SF2_PRESET: u16 = 0;
+    sf2_preset;
+    is_sf2_preset;
+}
+
+default! {
+    /// The default drum pad tuning in semitones
+    const PAD_TUNE: f32 = 0.0;
+    pad_tune;
+    is_pad_tune;
+}
+
+default! {
+    /// The default drum pad start time
+    const PAD_START: f32 = 0.0;
+    pad_start;
+    is_pad_start;
+}
+
+default! {
+    /// The default drum pad direction
+    const REVERSE: bool = false;
+    reverse;
+    is_reverse;
+}
+
+default! {
+    /// The default sequencer step length in seconds
+    const STEP: f32 = 0.125;
+    step;
+    is_step;
+}
+
+default! {
+    /// The default sequencer swing
+    const SWING: DynamicValue = DynamicValue::Static(0.0);
+    swing;
+    is_swing;
+}
+
+default! {
+    /// The default fraction of a step that notes are held for
+    const GATE: DynamicValue = DynamicValue::Static(0.5);
+    gate;
+    is_gate;
+}
+
+default! {
+    /// The default sequencer velocity
+    const VELOCITY: u8 = 100;
+    velocity;
+    is_velocity;
+}
+
+default! {
+    /// The default arpeggiator mode
+    const ARP_MODE: ArpMode = ArpMode::Up;
+    arp_mode;
+    is_arp_mode;
+}
+
+default! {
+    /// The default number of octaves an arpeggiator spans
+    const ARP_OCTAVES: u8 = 1;
+    arp_octaves;
+    is_arp_octaves;
+}
+
+default! {
+    /// The default note processor scale
+    const SCALE: ScaleDef = ScaleDef::Named(Scale::Chromatic);
+    scale;
+    is_scale;
+}
+
+default! {
+    /// The default scale root
+    const SCALE_ROOT: u8 = 0;
+    scale_root;
+    is_scale_root;
+}
+
+default! {
+    /// The default filter resonance
+    const RESONANCE: DynamicValue = DynamicValue::Static(0.707);
+    resonance;
+    is_resonance;
+}
+
+default! {
+    /// The default filter gain in decibels
+    const FILTER_GAIN: DynamicValue = DynamicValue::Static(0.0);
+    filter_gain;
+    is_filter_gain;
+}
+
+default! {
+    /// The default delay time in seconds
+    const DELAY_TIME: DynamicValue = DynamicValue::Static(0.25);
+    delay_time;
+    is_delay_time;
+}
+
+default! {
+    /// The default delay feedback
+    const FEEDBACK: DynamicValue = DynamicValue::Static(0.4);
+    feedback;
+    is_feedback;
+}
+
+default! {
+    /// The default tone of a delay's feedback
+    const TONE: DynamicValue = DynamicValue::Static(1.0);
+    tone;
+    is_tone;
+}
+
+default! {
+    /// The default ping-pong setting
+    const PING_PONG: bool = false;
+    ping_pong;
+    is_ping_pong;
+}
+
+default! {
+    /// The default modulation mode
+    const MODULATION_MODE: ModulationMode = ModulationMode::Chorus;
+    modulation_mode;
+    is_modulation_mode;
+}
+
+default! {
+    /// The default modulation rate in Hz
+    const RATE: DynamicValue = DynamicValue::Static(0.5);
+    rate;
+    is_rate;
+}
+
+default! {
+    /// The default modulation depth
+    const DEPTH: DynamicValue = DynamicValue::Static(0.5);
+    depth;
+    is_depth;
+}
+
+default! {
+    /// The default modulation feedback
+    const MODULATION_FEEDBACK: DynamicValue = DynamicValue::Static(0.0);
+    modulation_feedback;
+    is_modulation_feedback;
+}
+
+default! {
+    /// The default compressor threshold in decibels
+    const THRESHOLD: DynamicValue = DynamicValue::Static(-18.0);
+    threshold;
+    is_threshold;
+}
+
+default! {
+    /// The default compressor ratio
+    const RATIO: DynamicValue = DynamicValue::Static(4.0);
+    ratio;
+    is_ratio;
+}
+
+default! {
+    /// The default compressor attack time in seconds
+    const COMPRESSOR_ATTACK: DynamicValue = DynamicValue::Static(0.01);
+    compressor_attack;
+    is_compressor_attack;
+}
+
+default! {
+    /// The default compressor release time in seconds
+    const COMPRESSOR_RELEASE: DynamicValue = DynamicValue::Static(0.1);
+    compressor_release;
+    is_compressor_release;
+}
+
+default! {
+    /// The default compressor knee width in decibels
+    const KNEE: DynamicValue = DynamicValue::Static(6.0);
+    knee;
+    is_knee;
+}
+
+default! {
+    /// The default compressor makeup gain in decibels
+    const MAKEUP: DynamicValue = DynamicValue::Static(0.0);
+    makeup;
+    is_makeup;
+}
+
+default! {
+    /// The default limiter ceiling in decibels
+    const CEILING: f32 = -1.0;
+    ceiling;
+    is_ceiling;
+}
+
+default! {
+    /// The default limiter look-ahead time in seconds
+    const LOOKAHEAD: f32 = 0.005;
+    lookahead;
+    is_lookahead;
+}
+
+default! {
+    /// The default limiter release time in seconds
+    const LIMITER_RELEASE: f32 = 0.1;
+    limiter_release;
+    is_limiter_release;
+}
+
+default! {
+    /// The default equalizer band shape
+    const EQ_SHAPE: EqShape = EqShape::Peak;
+    eq_shape;
+    is_eq_shape;
+}
+
+default! {
+    /// The default bus send level
+    const LEVEL: DynamicValue = DynamicValue::Static(1.0);
+    level;
+    is_level;
+}
+
+default! {
+    /// The default gate threshold in decibels
+    const GATE_THRESHOLD: DynamicValue = DynamicValue::Static(-40.0);
+    gate_threshold;
+    is_gate_threshold;
+}
+
+default! {
+    /// The default gate attack time in seconds
+    const GATE_ATTACK: DynamicValue = DynamicValue::Static(0.001);
+    gate_attack;
+    is_gate_attack;
+}
+
+default! {
+    /// The default gate hold time in seconds
+    const HOLD: DynamicValue = DynamicValue::Static(0.05);
+    hold;
+    is_hold;
+}
+
+default! {
+    /// The default gate release time in seconds
+    const GATE_RELEASE: DynamicValue = DynamicValue::Static(0.1);
+    gate_release;
+    is_gate_release;
+}
+
+default! {
+    /// The default envelope follower attack time in seconds
+    const FOLLOWER_ATTACK: DynamicValue = DynamicValue::Static(0.01);
+    follower_attack;
+    is_follower_attack;
+}
+
+default! {
+    /// The default envelope follower release time in seconds
+    const FOLLOWER_RELEASE: DynamicValue = DynamicValue::Static(0.2);
+    follower_release;
+    is_follower_release;
+}
+
+default! {
+    /// The default pitch shift mode
+    const PITCH_SHIFT_MODE: PitchShiftMode = PitchShiftMode::Shift;
+    pitch_shift_mode;
+    is_pitch_shift_mode;
+}
+
+default! {
+    /// The default pitch shift in semitones
+    const SEMITONES: DynamicValue = DynamicValue::Static(0.0);
+    semitones;
+    is_semitones;
+}
+
+default! {
+    /// The default pitch shift in cents
+    const CENTS: DynamicValue = DynamicValue::Static(0.0);
+    cents;
+    is_cents;
+}
+
+default! {
+    /// The default crush bit depth
+    const BITS: DynamicValue = DynamicValue::Static(24.0);
+    bits;
+    is_bits;
+}
+
+default! {
+    /// The default crush anti-aliasing setting
+    const ANTI_ALIAS: bool = false;
+    anti_alias;
+    is_anti_alias;
+}
+
+default! {
+    /// The default filter dry/wet mix
+    const FILTER_MIX: DynamicValue = DynamicValue::Static(1.0);
+    filter_mix;
+    is_filter_mix;
+}
+
+default! {
+    /// The default distortion curve
+    const CURVE: DistortionCurve = DistortionCurve::Hard;
+    curve;
+    is_curve;
+}
+
+default! {
+    /// The default distortion drive
+    const DRIVE: DynamicValue = DynamicValue::Static(1.0);
+    drive;
+    is_drive;
+}
+
+default! {
+    /// The default distortion oversampling factor
+    const OVERSAMPLE: u8 = 1;
+    oversample;
+    is_oversample;
+}
+
+default! {
+    /// The default amount that a filter envelope adds to the resonance
+    const RESONANCE_ENV: DynamicValue = DynamicValue::Static(0.0);
+    resonance_env;
+    is_resonance_env;
+}
@@ -121,6 +121,29 @@ pub enum Spec {
             skip_serializing_if = "default::is_sustain_pedal"
         )]
         sustain_pedal: DynamicValue,
+        /// The maximum number of voices that can play at once
+        ///
+        /// If this field is not specified, the number of voices is unlimited
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        max_voices: Option<usize>,
+        /// The policy for choosing which voice to replace when `max_voices` is reached
+        #[serde(
+            default = "default::voice_steal",
+            skip_serializing_if = "default::is_voice_steal"
+        )]
+        steal: VoiceSteal,
+        /// Whether notes play polyphonically or on a single voice
+        #[serde(
+            default = "default::voice_mode",
+            skip_serializing_if = "default::is_voice_mode"
+        )]
+        voice_mode: VoiceMode,
+        /// The time in seconds it takes to glide between notes in the monophonic voice modes
+        #[serde(default = "default::glide", skip_serializing_if = "default::is_glide")]
+        glide: DynamicValue,
+        /// The tuning used to determine note frequencies
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        tuning: Option<TuningDef>,
     },
     /// A drum machine with a list of paths to sample files
     Drums {
@@ -128,12 +151,26 @@ pub enum Spec {
         paths: Option<Vec<PathBuf>>,
         /// The path to a folder containing audio files (relative to the ryvm samples directory)
         folder: Option<PathBuf>,
+        /// The method used to read between sample frames
+        #[serde(
+            default = "default::interpolation",
+            skip_serializing_if = "default::is_interpolation"
+        )]
+        interpolation: Interpolation,
+        /// The settings for each pad, in the same order as the samples
+        ///
+        /// Pads without settings use the defaults
+        #[serde(default, skip_serializing_if = "Vec::is_empty")]
+        pads: Vec<PadDef>,
     },
-    /// A low-pass filter
+    /// A filter
     Filter {
         /// The name of the input device
         input: Option<Name>,
         /// The value that determines the filter's shape
+        ///
+        /// For the resonant filter types, this maps the cutoff
+        /// from 20 Hz at 0 to 20 kHz at 1
         value: DynamicValue,
         /// The type of filter
         #[serde(
@@ -141,6 +178,66 @@ pub enum Spec {
             skip_serializing_if = "default::is_filter_type"
         )]
         filter: FilterType,
+        /// The resonance (Q) of the resonant filter types
+        #[serde(
+            default = "default::resonance",
+            skip_serializing_if = "default::is_resonance"
+        )]
+        resonance: DynamicValue,
+        /// How much the ADSR envelope adds to the resonance of the resonant filter types
+        ///
+        /// At the envelope's peak, the resonance is `resonance + resonance_env`
+        #[serde(
+            default = "default::resonance_env",
+            skip_serializing_if = "default::is_resonance_env"
+        )]
+        resonance_env: DynamicValue,
+        /// The gain in decibels of the peak and shelf filter types
+        #[serde(
+            default = "default::filter_gain",
+            skip_serializing_if = "default::is_filter_gain"
+        )]
+        gain: DynamicValue,
+        /// The bit depth of the crush filter type
+        #[serde(default = "default::bits", skip_serializing_if = "default::is_bits")]
+        bits: DynamicValue,
+        /// The ratio of the crush filter type's sample rate to the output sample rate
+        ///
+        /// If this field is not specified, the filter's value determines
+        /// how many frames each sample is held for
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        rate: Option<DynamicValue>,
+        /// Whether the crush filter type removes frequencies above its reduced sample rate
+        #[serde(
+            default = "default::anti_alias",
+            skip_serializing_if = "default::is_anti_alias"
+        )]
+        anti_alias: bool,
+        /// The curve of the distortion filter type
+        #[serde(default = "default::curve", skip_serializing_if = "default::is_curve")]
+        curve: DistortionCurve,
+        /// The linear gain applied before the distortion filter type's curve
+        #[serde(default = "default::drive", skip_serializing_if = "default::is_drive")]
+        drive: DynamicValue,
+        /// The cutoff of the low-pass filter after the distortion filter type's curve
+        ///
+        /// This maps the cutoff from 20 Hz at 0 to 20 kHz at 1
+        #[serde(default = "default::tone", skip_serializing_if = "default::is_tone")]
+        tone: DynamicValue,
+        /// The factor by which the distortion filter type oversamples to reduce aliasing
+        ///
+        /// This can be 1, 2, or 4
+        #[serde(
+            default = "default::oversample",
+            skip_serializing_if = "default::is_oversample"
+        )]
+        oversample: u8,
+        /// The balance between the dry and wet signals of the crush and distortion filter types
+        #[serde(
+            default = "default::filter_mix",
+            skip_serializing_if = "default::is_filter_mix"
+        )]
+        mix: DynamicValue,
         /// The optional ADSR envelope
         #[serde(default, skip_serializing_if = "Option::is_none")]
         adsr: Option<ADSR<DynamicValue>>,
@@ -150,6 +247,17 @@ pub enum Spec {
             skip_serializing_if = "default::is_sustain_pedal"
         )]
         sustain_pedal: DynamicValue,
+        /// The maximum number of voices that can play at once
+        ///
+        /// If this field is not specified, the number of voices is unlimited
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        max_voices: Option<usize>,
+        /// The policy for choosing which voice to replace when `max_voices` is reached
+        #[serde(
+            default = "default::voice_steal",
+            skip_serializing_if = "default::is_voice_steal"
+        )]
+        steal: VoiceSteal,
     },
     /// A volume and pan balancer
     Balance {
@@ -161,7 +269,7 @@ pub enum Spec {
             skip_serializing_if = "default::is_volume"
         )]
         volume: DynamicValue,
-        /// The left-right pan
+        /// The left-right pan from -1 (right) to 1 (left)
         #[serde(default = "default::pan", skip_serializing_if = "default::is_pan")]
         pan: DynamicValue,
     },
@@ -170,22 +278,49 @@ pub enum Spec {
         /// The name of the input device
         input: Option<Name>,
         /// The simulated room size
+        ///
+        /// 1 is a medium room. Larger sizes keep growing the room, more slowly the larger they get.
         #[serde(
             default = "default::room_size",
             skip_serializing_if = "default::is_room_size"
         )]
         size: DynamicValue,
-        /// The simulated energy multiplier
+        /// How long the reverb rings out, from 0 to 1
+        #[serde(
+            alias = "energy_mul",
+            default = "default::reverb_decay",
+            skip_serializing_if = "default::is_reverb_decay"
+        )]
+        decay: DynamicValue,
+        /// How much high frequencies are absorbed, from 0 to 1
+        #[serde(
+            default = "default::damping",
+            skip_serializing_if = "default::is_damping"
+        )]
+        damping: DynamicValue,
+        /// The time in seconds before the reverb starts
         #[serde(
-            default = "default::energy_mul",
-            skip_serializing_if = "default::is_energy_mul"
+            default = "default::pre_delay",
+            skip_serializing_if = "default::is_pre_delay"
         )]
-        energy_mul: DynamicValue,
+        pre_delay: DynamicValue,
+        /// The balance between the dry and wet signals
+        ///
+        /// At 0, only the dry signal is output. At 1, only the wet signal is output.
+        /// At 0.5, both are output at full volume.
+        #[serde(default = "default::mix", skip_serializing_if = "default::is_mix")]
+        mix: DynamicValue,
     },
     /// A pitch-changing sampler
     Sampler {
         /// The sample definition
         def: SampleDef,
+        /// The method used to read between sample frames
+        #[serde(
+            default = "default::interpolation",
+            skip_serializing_if = "default::is_interpolation"
+        )]
+        interpolation: Interpolation,
         /// The optional ADSR envelope
         #[serde(
             default = "default::adsr_env",
@@ -198,5 +333,449 @@ pub enum Spec {
             skip_serializing_if = "default::is_sustain_pedal"
         )]
         sustain_pedal: DynamicValue,
+        /// The maximum number of voices that can play at once
+        ///
+        /// If this field is not specified, the number of voices is unlimited
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        max_voices: Option<usize>,
+        /// The policy for choosing which voice to replace when `max_voices` is reached
+        #[serde(
+            default = "default::voice_steal",
+            skip_serializing_if = "default::is_voice_steal"
+        )]
+        steal: VoiceSteal,
+        /// Whether notes play polyphonically or on a single voice
+        #[serde(
+            default = "default::voice_mode",
+            skip_serializing_if = "default::is_voice_mode"
+        )]
+        voice_mode: VoiceMode,
+        /// The time in seconds it takes to glide between notes in the monophonic voice modes
+        #[serde(default = "default::glide", skip_serializing_if = "default::is_glide")]
+        glide: DynamicValue,
+        /// The tuning used to determine note frequencies
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        tuning: Option<TuningDef>,
+    },
+    /// A multi-sample instrument
+    ///
+    /// Zones can be loaded from an SFZ file, a SoundFont preset, listed inline, or any combination
+    Instrument {
+        /// The path to an SFZ file (relative to the ryvm samples directory)
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        sfz: Option<PathBuf>,
+        /// The path to a SoundFont 2 file (relative to the ryvm samples directory)
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        sf2: Option<PathBuf>,
+        /// The SoundFont bank number
+        #[serde(
+            default = "default::sf2_bank",
+            skip_serializing_if = "default::is_sf2_bank"
+        )]
+        bank: u16,
+        /// The SoundFont preset number
+        #[serde(
+            default = "default::sf2_preset",
+            skip_serializing_if = "default::is_sf2_preset"
+        )]
+        preset: u16,
+        /// The zones
+        #[serde(default, skip_serializing_if = "Vec::is_empty")]
+        zones: Vec<ZoneDef>,
+        /// The method used to read between sample frames
+        #[serde(
+            default = "default::interpolation",
+            skip_serializing_if = "default::is_interpolation"
+        )]
+        interpolation: Interpolation,
+        /// The ADSR envelope
+        ///
+        /// If this field is not specified, the SoundFont preset's volume envelope
+        /// is used if there is one
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        adsr: Option<ADSR<DynamicValue>>,
+        /// The sustain pedal control
+        #[serde(
+            default = "default::sustain_pedal",
+            skip_serializing_if = "default::is_sustain_pedal"
+        )]
+        sustain_pedal: DynamicValue,
+        /// The maximum number of voices that can play at once
+        ///
+        /// If this field is not specified, the number of voices is unlimited
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        max_voices: Option<usize>,
+        /// The policy for choosing which voice to replace when `max_voices` is reached
+        #[serde(
+            default = "default::voice_steal",
+            skip_serializing_if = "default::is_voice_steal"
+        )]
+        steal: VoiceSteal,
+        /// Whether notes play polyphonically or on a single voice
+        #[serde(
+            default = "default::voice_mode",
+            skip_serializing_if = "default::is_voice_mode"
+        )]
+        voice_mode: VoiceMode,
+        /// The time in seconds it takes to glide between notes in the monophonic voice modes
+        #[serde(default = "default::glide", skip_serializing_if = "default::is_glide")]
+        glide: DynamicValue,
+        /// The tuning used to determine note frequencies
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        tuning: Option<TuningDef>,
+    },
+    /// A plucked string synthesizer
+    Pluck {
+        /// The base octave
+        #[serde(
+            default = "default::octave",
+            skip_serializing_if = "default::is_octave"
+        )]
+        octave: i8,
+        /// The brightness of the string, from 0 to 1
+        #[serde(
+            default = "default::brightness",
+            skip_serializing_if = "default::is_brightness"
+        )]
+        brightness: DynamicValue,
+        /// The time in seconds it takes the string to decay by 60 dB
+        #[serde(
+            default = "default::pluck_decay",
+            skip_serializing_if = "default::is_pluck_decay"
+        )]
+        decay: DynamicValue,
+        /// The ADSR envelope
+        #[serde(
+            default = "default::adsr_env",
+            skip_serializing_if = "default::is_adsr_env"
+        )]
+        adsr: ADSR<DynamicValue>,
+        /// The +- pitch bend range in semitones
+        #[serde(
+            default = "default::bend_range",
+            skip_serializing_if = "default::is_bend_range"
+        )]
+        bend: DynamicValue,
+        /// The sustain pedal control
+        #[serde(
+            default = "default::sustain_pedal",
+            skip_serializing_if = "default::is_sustain_pedal"
+        )]
+        sustain_pedal: DynamicValue,
+        /// The maximum number of voices that can play at once
+        ///
+        /// If this field is not specified, the number of voices is unlimited
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        max_voices: Option<usize>,
+        /// The policy for choosing which voice to replace when `max_voices` is reached
+        #[serde(
+            default = "default::voice_steal",
+            skip_serializing_if = "default::is_voice_steal"
+        )]
+        steal: VoiceSteal,
+        /// The tuning used to determine note frequencies
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        tuning: Option<TuningDef>,
+    },
+    /// A step sequencer that plays drum pads or notes in its channel
+    Sequencer {
+        /// The tracks
+        tracks: Vec<StepTrackDef>,
+        /// The length of a step in seconds
+        #[serde(default = "default::step", skip_serializing_if = "default::is_step")]
+        step: f32,
+        /// The number of steps in one period of the loop master
+        ///
+        /// If this field is specified and there is a loop master,
+        /// steps are synced to it rather than using `step`
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        division: Option<u16>,
+        /// How far every second step is delayed, from 0 (straight) to 1 (half a step)
+        #[serde(default = "default::swing", skip_serializing_if = "default::is_swing")]
+        swing: DynamicValue,
+        /// The fraction of a step that notes are held for
+        #[serde(default = "default::gate", skip_serializing_if = "default::is_gate")]
+        gate: DynamicValue,
+    },
+    /// An arpeggiator that plays the notes held in its channel one at a time
+    Arp {
+        /// The order in which held notes are played
+        #[serde(
+            default = "default::arp_mode",
+            skip_serializing_if = "default::is_arp_mode"
+        )]
+        mode: ArpMode,
+        /// The length of a step in seconds
+        #[serde(default = "default::step", skip_serializing_if = "default::is_step")]
+        step: f32,
+        /// The number of steps in one period of the loop master
+        ///
+        /// If this field is specified and there is a loop master,
+        /// steps are synced to it rather than using `step`
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        division: Option<u16>,
+        /// The number of octaves the held notes are repeated over
+        #[serde(
+            default = "default::arp_octaves",
+            skip_serializing_if = "default::is_arp_octaves"
+        )]
+        octaves: u8,
+        /// The fraction of a step that notes are held for
+        #[serde(default = "default::gate", skip_serializing_if = "default::is_gate")]
+        gate: DynamicValue,
+    },
+    /// A note processor that locks the notes in its channel to a scale
+    /// and optionally expands them into chords
+    Notes {
+        /// The scale that notes are locked to
+        #[serde(default = "default::scale", skip_serializing_if = "default::is_scale")]
+        scale: ScaleDef,
+        /// The pitch class of the scale's root, where 0 is C
+        #[serde(
+            default = "default::scale_root",
+            skip_serializing_if = "default::is_scale_root"
+        )]
+        root: u8,
+        /// The chord that each note is expanded into
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        chord: Option<ChordDef>,
+    },
+    /// A delay that echoes its input
+    Delay {
+        /// The name of the input device
+        input: Option<Name>,
+        /// The delay time in seconds
+        #[serde(
+            default = "default::delay_time",
+            skip_serializing_if = "default::is_delay_time"
+        )]
+        time: DynamicValue,
+        /// The number of delays in one period of the loop master
+        ///
+        /// If this field is specified and there is a loop master,
+        /// the delay time is synced to it rather than using `time`
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        division: Option<f32>,
+        /// How much of each echo is fed back into the delay
+        #[serde(
+            default = "default::feedback",
+            skip_serializing_if = "default::is_feedback"
+        )]
+        feedback: DynamicValue,
+        /// The cutoff of the low-pass filter in the feedback path
+        ///
+        /// This maps from 20 Hz at 0 to 20 kHz at 1
+        #[serde(default = "default::tone", skip_serializing_if = "default::is_tone")]
+        tone: DynamicValue,
+        /// Whether echoes alternate between the left and right channels
+        #[serde(
+            default = "default::ping_pong",
+            skip_serializing_if = "default::is_ping_pong"
+        )]
+        ping_pong: bool,
+        /// The balance between the dry and wet signals
+        #[serde(default = "default::mix", skip_serializing_if = "default::is_mix")]
+        mix: DynamicValue,
+    },
+    /// A chorus, flanger, or phaser
+    Modulation {
+        /// The name of the input device
+        input: Option<Name>,
+        /// The type of modulation
+        #[serde(
+            default = "default::modulation_mode",
+            skip_serializing_if = "default::is_modulation_mode"
+        )]
+        mode: ModulationMode,
+        /// The rate of the modulation in Hz
+        #[serde(default = "default::rate", skip_serializing_if = "default::is_rate")]
+        rate: DynamicValue,
+        /// The depth of the modulation, from 0 to 1
+        #[serde(default = "default::depth", skip_serializing_if = "default::is_depth")]
+        depth: DynamicValue,
+        /// How much of the output is fed back into the effect, from -1 to 1
+        #[serde(
+            default = "default::modulation_feedback",
+            skip_serializing_if = "default::is_modulation_feedback"
+        )]
+        feedback: DynamicValue,
+        /// The balance between the dry and wet signals
+        #[serde(default = "default::mix", skip_serializing_if = "default::is_mix")]
+        mix: DynamicValue,
+    },
+    /// A compressor
+    Compressor {
+        /// The name of the input device
+        input: Option<Name>,
+        /// The name of a node whose level drives the gain reduction
+        ///
+        /// If this is not specified, the input's own level is used
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        sidechain: Option<Name>,
+        /// The level in decibels above which the input is compressed
+        #[serde(
+            default = "default::threshold",
+            skip_serializing_if = "default::is_threshold"
+        )]
+        threshold: DynamicValue,
+        /// The ratio of input level change to output level change above the threshold
+        #[serde(default = "default::ratio", skip_serializing_if = "default::is_ratio")]
+        ratio: DynamicValue,
+        /// The time in seconds it takes for the gain reduction to engage
+        #[serde(
+            default = "default::compressor_attack",
+            skip_serializing_if = "default::is_compressor_attack"
+        )]
+        attack: DynamicValue,
+        /// The time in seconds it takes for the gain reduction to recover
+        #[serde(
+            default = "default::compressor_release",
+            skip_serializing_if = "default::is_compressor_release"
+        )]
+        release: DynamicValue,
+        /// The width in decibels of the soft transition around the threshold
+        #[serde(default = "default::knee", skip_serializing_if = "default::is_knee")]
+        knee: DynamicValue,
+        /// The gain in decibels applied after compression
+        #[serde(
+            default = "default::makeup",
+            skip_serializing_if = "default::is_makeup"
+        )]
+        makeup: DynamicValue,
+    },
+    /// A limiter on the master output
+    ///
+    /// Only one limiter is used. Loading another one replaces it.
+    Limiter {
+        /// The maximum output level in decibels
+        #[serde(
+            default = "default::ceiling",
+            skip_serializing_if = "default::is_ceiling"
+        )]
+        ceiling: f32,
+        /// The time in seconds the limiter looks ahead for peaks
+        ///
+        /// The output is delayed by this amount
+        #[serde(
+            default = "default::lookahead",
+            skip_serializing_if = "default::is_lookahead"
+        )]
+        lookahead: f32,
+        /// The time in seconds it takes for the gain reduction to recover
+        #[serde(
+            default = "default::limiter_release",
+            skip_serializing_if = "default::is_limiter_release"
+        )]
+        release: f32,
+    },
+    /// A parametric equalizer
+    Eq {
+        /// The name of the input device
+        input: Option<Name>,
+        /// The bands of the equalizer, applied in order
+        bands: Vec<EqBand>,
+    },
+    /// A mixer that sums several inputs
+    Mix {
+        /// The inputs to sum
+        inputs: Vec<MixInput>,
+    },
+    /// Sends its input to a bus channel and passes it through unchanged
+    Send {
+        /// The name of the input device
+        input: Option<Name>,
+        /// The channel of the bus
+        ///
+        /// The bus channel should have a `return` node
+        bus: u8,
+        /// The linear gain of the sent signal
+        #[serde(default = "default::level", skip_serializing_if = "default::is_level")]
+        level: DynamicValue,
+    },
+    /// Outputs everything sent to this node's channel
+    ///
+    /// Channels with a return are mixed after all other channels.
+    /// A bus can only send to buses on higher channels.
+    Return,
+    /// A noise gate
+    Gate {
+        /// The name of the input device
+        input: Option<Name>,
+        /// The level in decibels below which the gate closes
+        #[serde(
+            default = "default::gate_threshold",
+            skip_serializing_if = "default::is_gate_threshold"
+        )]
+        threshold: DynamicValue,
+        /// The time in seconds it takes for the gate to open
+        #[serde(
+            default = "default::gate_attack",
+            skip_serializing_if = "default::is_gate_attack"
+        )]
+        attack: DynamicValue,
+        /// The time in seconds the gate stays open after the level drops below the threshold
+        #[serde(default = "default::hold", skip_serializing_if = "default::is_hold")]
+        hold: DynamicValue,
+        /// The time in seconds it takes for the gate to close
+        #[serde(
+            default = "default::gate_release",
+            skip_serializing_if = "default::is_gate_release"
+        )]
+        release: DynamicValue,
+    },
+    /// Outputs the amplitude envelope of its input
+    ///
+    /// This is meant to be used with `DynamicValue::Output`
+    Follower {
+        /// The name of the input device
+        input: Option<Name>,
+        /// The time in seconds it takes for the envelope to rise
+        #[serde(
+            default = "default::follower_attack",
+            skip_serializing_if = "default::is_follower_attack"
+        )]
+        attack: DynamicValue,
+        /// The time in seconds it takes for the envelope to fall
+        #[serde(
+            default = "default::follower_release",
+            skip_serializing_if = "default::is_follower_release"
+        )]
+        release: DynamicValue,
+        /// The linear gain applied to the envelope
+        #[serde(
+            default = "default::volume",
+            skip_serializing_if = "default::is_volume"
+        )]
+        gain: DynamicValue,
+    },
+    /// A pitch shifter and harmonizer
+    PitchShift {
+        /// The name of the input device
+        input: Option<Name>,
+        /// Whether to shift by a fixed interval or harmonize with held notes
+        #[serde(
+            default = "default::pitch_shift_mode",
+            skip_serializing_if = "default::is_pitch_shift_mode"
+        )]
+        mode: PitchShiftMode,
+        /// The shift in semitones
+        ///
+        /// When harmonizing, this transposes every voice
+        #[serde(
+            default = "default::semitones",
+            skip_serializing_if = "default::is_semitones"
+        )]
+        semitones: DynamicValue,
+        /// The fine shift in cents
+        #[serde(default = "default::cents", skip_serializing_if = "default::is_cents")]
+        cents: DynamicValue,
+        /// The note that is considered the pitch of the input when harmonizing
+        ///
+        /// Holding this note adds a voice at the input's pitch
+        #[serde(default = "default::root", skip_serializing_if = "default::is_root")]
+        root: u8,
+        /// The balance between the dry and wet signals
+        #[serde(default = "default::mix", skip_serializing_if = "default::is_mix")]
+        mix: DynamicValue,
     },
 }
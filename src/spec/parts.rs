@@ -46,6 +46,20 @@ pub enum FilterType {
     Crush,
     /// A distortion filter
     Distortion,
+    /// A low-pass filter with resonance
+    ResonantLowPass,
+    /// A high-pass filter with resonance
+    HighPass,
+    /// A band-pass filter
+    BandPass,
+    /// A band-stop filter
+    Notch,
+    /// A filter that boosts or cuts a band around the cutoff
+    Peak,
+    /// A filter that boosts or cuts frequencies below the cutoff
+    LowShelf,
+    /// A filter that boosts or cuts frequencies above the cutoff
+    HighShelf,
 }
 
 impl Default for FilterType {
@@ -53,6 +67,345 @@ impl Default for FilterType {
         FilterType::LowPass
     }
 }
+
+/// A method of reading between the frames of an audio sample
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Interpolation {
+    /// Use the nearest frame
+    Nearest,
+    /// Interpolate linearly between the two nearest frames
+    ///
+    /// This is the default interpolation
+    Linear,
+    /// Interpolate with a cubic curve through the four nearest frames
+    Cubic,
+}
+
+impl Default for Interpolation {
+    fn default() -> Self {
+        Interpolation::Linear
+    }
+}
+
+/// A policy for choosing which voice to replace when a node's voice limit is reached
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum VoiceSteal {
+    /// Replace the voice that has been playing the longest
+    ///
+    /// This is the default voice stealing policy
+    Oldest,
+    /// Replace the voice with the lowest amplitude
+    Quietest,
+    /// Retrigger a voice that is already playing the same note
+    ///
+    /// If there is no such voice, the oldest voice is replaced
+    SameNote,
+}
+
+impl Default for VoiceSteal {
+    fn default() -> Self {
+        VoiceSteal::Oldest
+    }
+}
+
+/// The way a node plays overlapping notes
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum VoiceMode {
+    /// Every note gets its own voice
+    ///
+    /// This is the default voice mode
+    Poly,
+    /// Notes play on a single voice, and each new note retriggers the envelope
+    Mono,
+    /// Notes play on a single voice, and overlapping notes do not retrigger the envelope
+    Legato,
+}
+
+impl Default for VoiceMode {
+    fn default() -> Self {
+        VoiceMode::Poly
+    }
+}
+
+/// The order in which an arpeggiator plays held notes
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ArpMode {
+    /// From lowest to highest
+    ///
+    /// This is the default arpeggiator mode
+    Up,
+    /// From highest to lowest
+    Down,
+    /// From lowest to highest and back down
+    UpDown,
+    /// In a random order
+    Random,
+    /// In the order the notes were played
+    Played,
+}
+
+impl Default for ArpMode {
+    fn default() -> Self {
+        ArpMode::Up
+    }
+}
+
+/// The curve that the distortion filter type shapes its input with
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DistortionCurve {
+    /// Clip the input
+    ///
+    /// This is the default distortion curve
+    Hard,
+    /// A smooth hyperbolic tangent saturation
+    Tanh,
+    /// A smooth arctangent saturation, softer than `tanh`
+    Arctan,
+    /// Fold the input back on itself when it passes the limits
+    Foldback,
+    /// An asymmetric saturation that adds even harmonics
+    Tube,
+    /// A user-defined curve
+    ///
+    /// The points are evenly spaced over inputs from -1 to 1 and linearly interpolated
+    Table(Vec<f32>),
+}
+
+impl Default for DistortionCurve {
+    fn default() -> Self {
+        DistortionCurve::Hard
+    }
+}
+
+/// A type of modulation effect
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ModulationMode {
+    /// A slowly modulated delay that thickens the sound
+    ///
+    /// This is the default modulation mode
+    Chorus,
+    /// A short modulated delay that creates a sweeping comb filter
+    Flanger,
+    /// A chain of modulated allpass filters that creates sweeping notches
+    Phaser,
+}
+
+impl Default for ModulationMode {
+    fn default() -> Self {
+        ModulationMode::Chorus
+    }
+}
+
+/// A type of pitch shifting
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PitchShiftMode {
+    /// Shift the input by a fixed interval
+    ///
+    /// This is the default pitch shift mode
+    Shift,
+    /// Add a shifted voice for each note held on the channel
+    Harmonize,
+}
+
+impl Default for PitchShiftMode {
+    fn default() -> Self {
+        PitchShiftMode::Shift
+    }
+}
+
+/// An input to a mixer
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct MixInput {
+    /// The name of the input node
+    pub input: Name,
+    /// The linear gain
+    #[serde(
+        default = "default::volume",
+        skip_serializing_if = "default::is_volume"
+    )]
+    pub gain: DynamicValue,
+    /// The stereo position from -1 (right) to 1 (left)
+    #[serde(default = "default::pan", skip_serializing_if = "default::is_pan")]
+    pub pan: DynamicValue,
+}
+
+/// The shape of an equalizer band
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum EqShape {
+    /// Boosts or cuts frequencies around the band frequency
+    ///
+    /// This is the default band shape
+    Peak,
+    /// Boosts or cuts frequencies below the band frequency
+    LowShelf,
+    /// Boosts or cuts frequencies above the band frequency
+    HighShelf,
+    /// Removes frequencies above the band frequency
+    LowPass,
+    /// Removes frequencies below the band frequency
+    HighPass,
+    /// Removes frequencies away from the band frequency
+    BandPass,
+    /// Removes frequencies around the band frequency
+    Notch,
+}
+
+impl Default for EqShape {
+    fn default() -> Self {
+        EqShape::Peak
+    }
+}
+
+/// A band of an equalizer
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct EqBand {
+    /// The shape of the band
+    #[serde(
+        default = "default::eq_shape",
+        skip_serializing_if = "default::is_eq_shape"
+    )]
+    pub shape: EqShape,
+    /// The center or corner frequency in Hz
+    pub freq: DynamicValue,
+    /// The boost or cut in decibels
+    ///
+    /// This only affects peak and shelf bands
+    #[serde(
+        default = "default::filter_gain",
+        skip_serializing_if = "default::is_filter_gain"
+    )]
+    pub gain: DynamicValue,
+    /// The width of the band
+    #[serde(
+        default = "default::resonance",
+        skip_serializing_if = "default::is_resonance"
+    )]
+    pub q: DynamicValue,
+}
+
+/// A named musical scale
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+#[allow(missing_docs)]
+pub enum Scale {
+    Chromatic,
+    Major,
+    Minor,
+    HarmonicMinor,
+    MelodicMinor,
+    Dorian,
+    Phrygian,
+    Lydian,
+    Mixolydian,
+    Locrian,
+    MajorPentatonic,
+    MinorPentatonic,
+    Blues,
+}
+
+impl Scale {
+    /// Get the semitones of each degree of the scale above the root
+    pub fn steps(self) -> &'static [u8] {
+        match self {
+            Scale::Chromatic => &[0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11],
+            Scale::Major => &[0, 2, 4, 5, 7, 9, 11],
+            Scale::Minor => &[0, 2, 3, 5, 7, 8, 10],
+            Scale::HarmonicMinor => &[0, 2, 3, 5, 7, 8, 11],
+            Scale::MelodicMinor => &[0, 2, 3, 5, 7, 9, 11],
+            Scale::Dorian => &[0, 2, 3, 5, 7, 9, 10],
+            Scale::Phrygian => &[0, 1, 3, 5, 7, 8, 10],
+            Scale::Lydian => &[0, 2, 4, 6, 7, 9, 11],
+            Scale::Mixolydian => &[0, 2, 4, 5, 7, 9, 10],
+            Scale::Locrian => &[0, 1, 3, 5, 6, 8, 10],
+            Scale::MajorPentatonic => &[0, 2, 4, 7, 9],
+            Scale::MinorPentatonic => &[0, 3, 5, 7, 10],
+            Scale::Blues => &[0, 3, 5, 6, 7, 10],
+        }
+    }
+}
+
+/// A scale that is either named or a list of semitones above the root
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum ScaleDef {
+    /// A named scale
+    Named(Scale),
+    /// A list of semitones above the root
+    Custom(Vec<u8>),
+}
+
+impl ScaleDef {
+    /// Get the sorted semitones of each degree of the scale above the root
+    ///
+    /// An empty custom scale is treated as chromatic
+    pub fn steps(&self) -> Vec<u8> {
+        match self {
+            ScaleDef::Named(scale) => scale.steps().to_vec(),
+            ScaleDef::Custom(steps) => {
+                let mut steps: Vec<u8> = steps.iter().map(|step| step % 12).collect();
+                steps.sort_unstable();
+                steps.dedup();
+                if steps.is_empty() {
+                    Scale::Chromatic.steps().to_vec()
+                } else {
+                    steps
+                }
+            }
+        }
+    }
+}
+
+/// A named chord shape
+///
+/// Chords are built from the degrees of the scale.
+/// With the chromatic scale, the major scale is used.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+#[allow(missing_docs)]
+pub enum Chord {
+    Triad,
+    Seventh,
+    Ninth,
+    Power,
+    Sus2,
+    Sus4,
+}
+
+impl Chord {
+    /// Get the scale degrees of each chord note above the played note
+    pub fn degrees(self) -> &'static [u8] {
+        match self {
+            Chord::Triad => &[0, 2, 4],
+            Chord::Seventh => &[0, 2, 4, 6],
+            Chord::Ninth => &[0, 2, 4, 6, 8],
+            Chord::Power => &[0, 4],
+            Chord::Sus2 => &[0, 1, 4],
+            Chord::Sus4 => &[0, 3, 4],
+        }
+    }
+}
+
+/// A chord that is either named or a list of semitones above the played note
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum ChordDef {
+    /// A named chord built from the scale
+    Named(Chord),
+    /// A list of semitones above the played note
+    ///
+    /// These notes are not locked to the scale
+    Custom(Vec<i8>),
+}
 /// A type of filter
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 #[serde(rename_all = "snake_case")]
@@ -147,6 +500,8 @@ pub enum DynamicValue {
         default: Option<f32>,
     },
     /// The value output by another node
+    ///
+    /// Nodes in other channels can be referenced with `channel:name`, like `3:lfo`
     Output(Name),
 }
 
@@ -243,9 +598,156 @@ impl ADSR<DynamicValue> {
     }
 }
 
+/// A sample that plays over a range of notes and velocities
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ZoneDef {
+    /// The path to the sample audio file (relative to the ryvm samples directory)
+    pub path: PathBuf,
+    /// The lowest and highest notes that play this zone
+    #[serde(
+        default = "default::zone_range",
+        skip_serializing_if = "default::is_zone_range"
+    )]
+    pub keys: (u8, u8),
+    /// The lowest and highest velocities that play this zone
+    #[serde(
+        default = "default::zone_range",
+        skip_serializing_if = "default::is_zone_range"
+    )]
+    pub velocities: (u8, u8),
+    /// The note at which the sample plays at its original pitch
+    #[serde(default = "default::root", skip_serializing_if = "default::is_root")]
+    pub root: u8,
+    /// The fine tuning in cents
+    #[serde(
+        default = "default::zone_tune",
+        skip_serializing_if = "default::is_zone_tune"
+    )]
+    pub tune: f32,
+    /// The start and end of the loop in sample frames
+    ///
+    /// If this field is not specified, the sample does not loop
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub loop_frames: Option<(u64, u64)>,
+    /// The linear gain
+    #[serde(default = "default::gain", skip_serializing_if = "default::is_gain")]
+    pub gain: f32,
+    /// The stereo position from -1 (right) to 1 (left)
+    #[serde(
+        default = "default::zone_pan",
+        skip_serializing_if = "default::is_zone_pan"
+    )]
+    pub pan: f32,
+}
+
+impl ZoneDef {
+    /// Check if a note and velocity play this zone
+    pub fn contains(&self, note: u8, velocity: u8) -> bool {
+        (self.keys.0..=self.keys.1).contains(&note)
+            && (self.velocities.0..=self.velocities.1).contains(&velocity)
+    }
+}
+
+/// The settings for a single drum pad
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct PadDef {
+    /// The linear gain
+    #[serde(default = "default::gain", skip_serializing_if = "default::is_gain")]
+    pub gain: f32,
+    /// The stereo position from -1 (right) to 1 (left)
+    #[serde(
+        default = "default::zone_pan",
+        skip_serializing_if = "default::is_zone_pan"
+    )]
+    pub pan: f32,
+    /// The tuning in semitones
+    #[serde(
+        default = "default::pad_tune",
+        skip_serializing_if = "default::is_pad_tune"
+    )]
+    pub tune: f32,
+    /// The time in seconds into the sample at which playback starts
+    #[serde(
+        default = "default::pad_start",
+        skip_serializing_if = "default::is_pad_start"
+    )]
+    pub start: f32,
+    /// Whether the sample plays backwards
+    #[serde(
+        default = "default::reverse",
+        skip_serializing_if = "default::is_reverse"
+    )]
+    pub reverse: bool,
+    /// The choke group
+    ///
+    /// Triggering a pad cuts off all playing pads in the same choke group
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub choke: Option<u8>,
+}
+
+impl Default for PadDef {
+    fn default() -> Self {
+        PadDef {
+            gain: default::GAIN,
+            pan: default::ZONE_PAN,
+            tune: default::PAD_TUNE,
+            start: default::PAD_START,
+            reverse: default::REVERSE,
+            choke: None,
+        }
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct SampleDef {
     pub path: PathBuf,
     pub loop_start: f32,
     pub pitch: f32,
 }
+
+/// A tuning that maps notes to frequencies
+///
+/// Scala `.scl` and `.kbm` files are supported. Without a scale file,
+/// 12-tone equal temperament is used.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct TuningDef {
+    /// The path to a Scala scale file (relative to the ryvm tunings directory)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub scale: Option<PathBuf>,
+    /// The path to a Scala keyboard mapping file (relative to the ryvm tunings directory)
+    ///
+    /// If this field is not specified, scale degree 0 is mapped to note 48
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub keyboard: Option<PathBuf>,
+    /// The frequency of the reference note in Hz
+    ///
+    /// This overrides the reference frequency of the keyboard mapping
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub reference: Option<f32>,
+}
+
+/// A single track of a step sequencer
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct StepTrackDef {
+    /// The drum pad that this track triggers
+    ///
+    /// If neither this nor `note` is specified, the pad matching
+    /// the track's position in the list is triggered
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub pad: Option<u8>,
+    /// The note that this track plays
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub note: Option<u8>,
+    /// The pattern of steps, where `-` is a hit and `_` is a rest
+    pub pattern: String,
+    /// The velocity of each hit
+    #[serde(
+        default = "default::velocity",
+        skip_serializing_if = "default::is_velocity"
+    )]
+    pub velocity: u8,
+}
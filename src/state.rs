@@ -18,10 +18,11 @@ use structopt::StructOpt;
 use crate::{
     app,
     channel::{Channel, FrameCache},
-    colorprintln, input, library, midi, node, onfly,
+    colorprintln, dsp, input, library, midi, node, onfly,
     r#loop::{Loop, LoopMaster, LoopState},
-    sample,
+    sample, sf2, sfz,
     spec::{self, Spec},
+    tuning::Tuning,
     ty::{Control, Frame, Name, Port, Voice},
     utility,
 };
@@ -71,6 +72,14 @@ pub struct State {
     input_manager: input::InputManager,
     inputs: HashMap<Name, input::InputDevice>,
     default_input: Option<Name>,
+    /// The master limiter and the name of the spec that created it
+    limiter: Option<(Name, dsp::Limiter)>,
+    /// The spec map that the master limiter was loaded from
+    limiter_map: Option<PathBuf>,
+    /// The output node names of each channel, in the order they are mixed
+    outputs: Vec<(u8, Vec<Name>)>,
+    /// Whether the channels have changed since `outputs` was last built
+    outputs_dirty: bool,
 }
 
 impl State {
@@ -119,6 +128,10 @@ impl State {
             input_manager: input::InputManager::new(),
             inputs: HashMap::new(),
             default_input: None,
+            limiter: None,
+            limiter_map: None,
+            outputs: Vec::new(),
+            outputs_dirty: true,
         };
         // Load startup
         if let Err(e) = state.load_spec_map(library::startup_path()?, None, true) {
@@ -146,6 +159,13 @@ impl State {
             self.vars.i % (self.vars.sample_rate as Frame / 5) == 0
         }
     }
+    /// Get the current time of the loop master within its period
+    pub fn loop_master_t(&self) -> Option<f32> {
+        let master = self.loop_master?;
+        self.loops
+            .get(&master.num)
+            .map(|lup| (lup.t() % master.period).max(0.0))
+    }
     /// Start a loop
     pub fn start_loop(&mut self, loop_num: Option<u8>, length: Option<f32>) {
         if loop_num.is_some() {
@@ -204,8 +224,16 @@ impl State {
     fn stop_loop(&mut self, num: u8) {
         if let Some(lup) = self.loops.get_mut(&num) {
             for id in lup.note_ids() {
-                for node in self.channels.values_mut().flat_map(Channel::nodes_mut) {
-                    node.end_envelopes(id);
+                for channel in self.channels.values_mut() {
+                    // Also end notes derived from this one, such as chord notes
+                    let ids: Vec<u64> = once(id)
+                        .chain(channel.nodes().flat_map(|node| node.derived_note_ids(id)))
+                        .collect();
+                    for node in channel.nodes_mut() {
+                        for &id in &ids {
+                            node.end_envelopes(id);
+                        }
+                    }
                 }
             }
             lup.loop_state = LoopState::Disabled;
@@ -231,6 +259,8 @@ impl State {
         last_name: Option<Name>,
         do_load_specs: bool,
     ) -> crate::Result<()> {
+        self.outputs_dirty = true;
+        let sample_rate = self.vars.sample_rate;
         // Macro for initializting nodes
         macro_rules! node {
             ($variant:ident, $default:expr) => {{
@@ -362,6 +392,16 @@ impl State {
                 self.inputs.insert(name, input);
                 self.default_input.get_or_insert(name);
             }
+            Spec::Limiter {
+                ceiling,
+                lookahead,
+                release,
+            } => {
+                self.limiter = Some((
+                    name,
+                    dsp::Limiter::new(sample_rate, ceiling, lookahead, release),
+                ));
+            }
             Spec::InputPass { input } => {
                 let input = input
                     .or(self.default_input)
@@ -378,7 +418,15 @@ impl State {
                 release,
                 bend,
                 sustain_pedal,
+                max_voices,
+                steal,
+                voice_mode,
+                glide,
+                tuning,
             } => {
+                let tuning = tuning
+                    .as_ref()
+                    .map_or_else(|| Ok(Tuning::default()), Tuning::load)?;
                 let wave = node!(Wave, || node::Wave::new(form));
                 wave.form = form;
                 wave.octave = octave.into();
@@ -388,8 +436,18 @@ impl State {
                 wave.adsr.release = release;
                 wave.pitch_bend_range = bend;
                 wave.sustain_pedal = sustain_pedal;
+                wave.voicing.max_voices = max_voices;
+                wave.voicing.steal = steal;
+                wave.voicing.mode = voice_mode;
+                wave.glide = glide;
+                wave.tuning = tuning;
             }
-            Spec::Drums { paths, folder } => {
+            Spec::Drums {
+                paths,
+                folder,
+                interpolation,
+                pads,
+            } => {
                 let drums = node!(DrumMachine, || node::DrumMachine::new());
                 let paths = if let Some(folder) = folder {
                     let folder = library::samples_dir()?.join(folder);
@@ -400,6 +458,8 @@ impl State {
                             paths.push(path);
                         }
                     }
+                    // Sort so that pad settings match up with samples
+                    paths.sort();
                     paths
                 } else if let Some(paths) = paths {
                     paths
@@ -410,21 +470,49 @@ impl State {
                     self.sample_bank.start(path);
                 }
                 drums.samples = paths;
+                drums.interpolation = interpolation;
+                drums.pads = pads;
             }
             Spec::Filter {
                 input,
                 value,
                 filter: filter_type,
+                resonance,
+                resonance_env,
+                gain,
+                bits,
+                rate,
+                anti_alias,
+                curve,
+                drive,
+                tone,
+                oversample,
+                mix,
                 adsr,
                 sustain_pedal,
+                max_voices,
+                steal,
             } => {
                 let input = get_input!(input);
                 let filter = node!(Filter, || node::Filter::new(input, value, filter_type));
                 filter.input = input;
                 filter.value = value;
                 filter.set_type(filter_type);
+                filter.resonance = resonance;
+                filter.resonance_env = resonance_env;
+                filter.gain = gain;
+                filter.bits = bits;
+                filter.rate = rate;
+                filter.anti_alias = anti_alias;
+                filter.curve = curve;
+                filter.drive = drive;
+                filter.tone = tone;
+                filter.oversample = oversample;
+                filter.mix = mix;
                 filter.adsr = adsr;
                 filter.sustain_pedal = sustain_pedal;
+                filter.voicing.max_voices = max_voices;
+                filter.voicing.steal = steal;
             }
             Spec::Balance { input, volume, pan } => {
                 let input = get_input!(input);
@@ -436,23 +524,285 @@ impl State {
             Spec::Reverb {
                 input,
                 size,
-                energy_mul,
+                decay,
+                damping,
+                pre_delay,
+                mix,
             } => {
                 let input = get_input!(input);
-                let reverb = node!(Reverb, || node::Reverb::new(input));
+                let reverb = node!(Reverb, || node::Reverb::new(input, sample_rate));
                 reverb.size = size;
-                reverb.energy_mul = energy_mul;
+                reverb.decay = decay;
+                reverb.damping = damping;
+                reverb.pre_delay = pre_delay;
+                reverb.mix = mix;
             }
             Spec::Sampler {
                 def,
+                interpolation,
                 adsr,
                 sustain_pedal,
+                max_voices,
+                steal,
+                voice_mode,
+                glide,
+                tuning,
             } => {
+                let tuning = tuning
+                    .as_ref()
+                    .map_or_else(|| Ok(Tuning::default()), Tuning::load)?;
                 self.sample_bank.start(def.path.clone());
                 let sampler = node!(Sampler, || node::Sampler::new(def.clone()));
                 sampler.def = def;
+                sampler.interpolation = interpolation;
                 sampler.adsr = adsr;
                 sampler.sustain_pedal = sustain_pedal;
+                sampler.voicing.max_voices = max_voices;
+                sampler.voicing.steal = steal;
+                sampler.voicing.mode = voice_mode;
+                sampler.glide = glide;
+                sampler.tuning = tuning;
+            }
+            Spec::Instrument {
+                sfz,
+                sf2,
+                bank,
+                preset,
+                zones,
+                interpolation,
+                adsr,
+                sustain_pedal,
+                max_voices,
+                steal,
+                voice_mode,
+                glide,
+                tuning,
+            } => {
+                let tuning = tuning
+                    .as_ref()
+                    .map_or_else(|| Ok(Tuning::default()), Tuning::load)?;
+                let mut all_zones = if let Some(sfz) = sfz {
+                    sfz::load(&sfz)?
+                } else {
+                    Vec::new()
+                };
+                let mut sf2_adsr = None;
+                if let Some(sf2) = sf2 {
+                    let (sf2_zones, envelope) = sf2::load_zones(&sf2, bank, preset)?;
+                    all_zones.extend(sf2_zones);
+                    sf2_adsr = envelope;
+                }
+                all_zones.extend(zones);
+                for zone in &all_zones {
+                    self.sample_bank.start(zone.path.clone());
+                }
+                let inst = node!(Instrument, || node::Instrument::new());
+                inst.zones = all_zones;
+                inst.interpolation = interpolation;
+                inst.adsr = adsr.unwrap_or_else(|| {
+                    sf2_adsr
+                        .unwrap_or_default()
+                        .map(|f| spec::DynamicValue::Static(*f))
+                });
+                inst.sustain_pedal = sustain_pedal;
+                inst.voicing.max_voices = max_voices;
+                inst.voicing.steal = steal;
+                inst.voicing.mode = voice_mode;
+                inst.glide = glide;
+                inst.tuning = tuning;
+            }
+            Spec::Pluck {
+                octave,
+                brightness,
+                decay,
+                adsr,
+                bend,
+                sustain_pedal,
+                max_voices,
+                steal,
+                tuning,
+            } => {
+                let tuning = tuning
+                    .as_ref()
+                    .map_or_else(|| Ok(Tuning::default()), Tuning::load)?;
+                let pluck = node!(Pluck, || node::Pluck::new());
+                pluck.octave = octave;
+                pluck.brightness = brightness;
+                pluck.decay = decay;
+                pluck.adsr = adsr;
+                pluck.pitch_bend_range = bend;
+                pluck.sustain_pedal = sustain_pedal;
+                pluck.voicing.max_voices = max_voices;
+                pluck.voicing.steal = steal;
+                pluck.tuning = tuning;
+            }
+            Spec::Sequencer {
+                tracks,
+                step,
+                division,
+                swing,
+                gate,
+            } => {
+                let seq = node!(Sequencer, || node::Sequencer::new());
+                seq.tracks = tracks
+                    .into_iter()
+                    .enumerate()
+                    .map(|(i, track)| node::StepTrack {
+                        target: match (track.pad, track.note) {
+                            (Some(pad), _) => node::StepTarget::Pad(pad),
+                            (None, Some(note)) => node::StepTarget::Note(note),
+                            (None, None) => node::StepTarget::Pad(i as u8),
+                        },
+                        pattern: track.pattern.parse().unwrap_or_else(|e| match e {}),
+                        velocity: track.velocity,
+                    })
+                    .collect();
+                seq.step = step;
+                seq.division = division;
+                seq.swing = swing;
+                seq.gate = gate;
+            }
+            Spec::Arp {
+                mode,
+                step,
+                division,
+                octaves,
+                gate,
+            } => {
+                let arp = node!(Arp, || node::Arp::new());
+                arp.mode = mode;
+                arp.step = step;
+                arp.division = division;
+                arp.octaves = octaves;
+                arp.gate = gate;
+            }
+            Spec::Delay {
+                input,
+                time,
+                division,
+                feedback,
+                tone,
+                ping_pong,
+                mix,
+            } => {
+                let input = get_input!(input);
+                let delay = node!(Delay, || node::Delay::new(input, sample_rate));
+                delay.input = input;
+                delay.time = time;
+                delay.division = division;
+                delay.feedback = feedback;
+                delay.tone = tone;
+                delay.ping_pong = ping_pong;
+                delay.mix = mix;
+            }
+            Spec::Modulation {
+                input,
+                mode,
+                rate,
+                depth,
+                feedback,
+                mix,
+            } => {
+                let input = get_input!(input);
+                let modulation = node!(Modulation, || node::Modulation::new(input, sample_rate));
+                modulation.input = input;
+                modulation.mode = mode;
+                modulation.rate = rate;
+                modulation.depth = depth;
+                modulation.feedback = feedback;
+                modulation.mix = mix;
+            }
+            Spec::Compressor {
+                input,
+                sidechain,
+                threshold,
+                ratio,
+                attack,
+                release,
+                knee,
+                makeup,
+            } => {
+                let input = get_input!(input);
+                let compressor = node!(Compressor, || node::Compressor::new(input));
+                compressor.input = input;
+                compressor.sidechain = sidechain;
+                compressor.threshold = threshold;
+                compressor.ratio = ratio;
+                compressor.attack = attack;
+                compressor.release = release;
+                compressor.knee = knee;
+                compressor.makeup = makeup;
+            }
+            Spec::Eq { input, bands } => {
+                let input = get_input!(input);
+                let eq = node!(Equalizer, || node::Equalizer::new(input));
+                eq.input = input;
+                eq.set_bands(bands);
+            }
+            Spec::Mix { inputs } => {
+                let mixer = node!(Mixer, || node::Mixer::new());
+                mixer.inputs = inputs;
+            }
+            Spec::Send { input, bus, level } => {
+                let input = get_input!(input);
+                let send = node!(BusSend, || node::BusSend::new(input, bus));
+                send.input = input;
+                send.bus = bus;
+                send.level = level;
+            }
+            Spec::Return => {
+                node!(BusReturn, || node::BusReturn);
+            }
+            Spec::Gate {
+                input,
+                threshold,
+                attack,
+                hold,
+                release,
+            } => {
+                let input = get_input!(input);
+                let gate = node!(Gate, || node::Gate::new(input));
+                gate.input = input;
+                gate.threshold = threshold;
+                gate.attack = attack;
+                gate.hold = hold;
+                gate.release = release;
+            }
+            Spec::Follower {
+                input,
+                attack,
+                release,
+                gain,
+            } => {
+                let input = get_input!(input);
+                let follower = node!(Follower, || node::Follower::new(input));
+                follower.input = input;
+                follower.attack = attack;
+                follower.release = release;
+                follower.gain = gain;
+            }
+            Spec::PitchShift {
+                input,
+                mode,
+                semitones,
+                cents,
+                root,
+                mix,
+            } => {
+                let input = get_input!(input);
+                let shift = node!(PitchShift, || node::PitchShift::new(input, sample_rate));
+                shift.input = input;
+                shift.mode = mode;
+                shift.semitones = semitones;
+                shift.cents = cents;
+                shift.root = root;
+                shift.mix = mix;
+            }
+            Spec::Notes { scale, root, chord } => {
+                let notes = node!(NoteProcessor, || node::NoteProcessor::new());
+                notes.steps = scale.steps();
+                notes.root = root;
+                notes.chord = chord;
             }
         }
         Ok(())
@@ -528,8 +878,7 @@ impl State {
             app::RyvmCommand::Tree => {
                 for (ch, channel) in self.channels.iter().sorted_by_key(|(ch, _)| *ch) {
                     println!("~~~~ Channel {} ~~~~", ch);
-                    let outputs: Vec<String> = channel.outputs().map(Into::into).collect();
-                    for output in outputs {
+                    for output in self.channel_outputs(*ch, channel) {
                         self.print_tree(*ch, &output, 0);
                     }
                 }
@@ -540,6 +889,7 @@ impl State {
                 channel,
                 recursive,
             } => {
+                self.outputs_dirty = true;
                 if let Some(ch) = channel {
                     if let Some(channel) = self.channels.get_mut(&ch) {
                         channel.remove(&id, recursive);
@@ -549,6 +899,10 @@ impl State {
                         channel.remove(&id, recursive);
                     }
                 }
+                if matches!(&self.limiter, Some((name, _)) if name.as_str() == id) {
+                    self.limiter = None;
+                    self.limiter_map = None;
+                }
                 if let Ok(num) = id.parse::<u8>() {
                     self.stop_loop(num);
                     self.loops.remove(&num);
@@ -569,6 +923,9 @@ impl State {
             app::RyvmCommand::Loops => {
                 open::that(library::loops_dir()?)?;
             }
+            app::RyvmCommand::Tunings => {
+                open::that(library::tunings_dir()?)?;
+            }
             app::RyvmCommand::Inputs => utility::list_input_devices()?,
             app::RyvmCommand::Output(app::OutputSubcommand::List) => {
                 utility::list_output_devices()?
@@ -600,10 +957,21 @@ impl State {
         // Deserialize the data
         let specs = toml::from_slice::<IndexMap<Name, Spec>>(&bytes)?;
 
+        // Remove the limiter if this map created it but no longer contains it
+        let has_limiter = specs
+            .values()
+            .any(|spec| matches!(spec, Spec::Limiter { .. }));
+        if has_limiter {
+            self.limiter_map = Some(path.clone());
+        } else if self.limiter_map.as_ref() == Some(&path) {
+            self.limiter = None;
+            self.limiter_map = None;
+        }
         // Add the path to the list of tracked maps
         self.tracked_spec_maps.insert(path, channel);
         if let Some(ch) = channel {
             // Remove specs no longer present for this channel
+            self.outputs_dirty = true;
             let channel = self.channels.entry(ch).or_insert_with(Channel::default);
             channel.retain(|name, _| specs.contains_key(name));
         }
@@ -731,12 +1099,14 @@ impl State {
         }
     }
     fn print_tree(&self, ch: u8, root: &str, depth: usize) {
+        // Follow references to nodes in other channels
+        let (ch, name) = utility::split_node_ref(root).unwrap_or((ch, root));
         let channel = if let Some(channel) = self.channels.get(&ch) {
             channel
         } else {
             return;
         };
-        let exists = channel.get(root).is_some();
+        let exists = channel.get(name).is_some();
         print!(
             "{}{}{}",
             (0..(2 * depth)).map(|_| ' ').collect::<String>(),
@@ -744,12 +1114,59 @@ impl State {
             if exists { "" } else { "?" }
         );
         println!();
-        if let Some(dev) = channel.get(&root) {
-            for input in dev.inputs().into_iter().map(Into::<String>::into).sorted() {
+        if let Some(dev) = channel.get(name) {
+            for input in dev
+                .inputs()
+                .into_iter()
+                .chain(dev.references())
+                .map(Into::<String>::into)
+                .sorted()
+            {
                 self.print_tree(ch, &input, depth + 1);
             }
         }
     }
+    /// Get a channel
+    #[must_use]
+    pub fn channel(&self, num: u8) -> Option<&Channel> {
+        self.channels.get(&num)
+    }
+    /// Get the names of the nodes in a channel that should be output
+    ///
+    /// This excludes nodes whose audio is processed by nodes in other channels
+    fn channel_outputs(&self, channel_num: u8, channel: &Channel) -> Vec<Name> {
+        let referenced: HashSet<&str> = self
+            .channels
+            .values()
+            .flat_map(Channel::nodes)
+            .flat_map(node::Node::audio_inputs)
+            .filter_map(utility::split_node_ref)
+            .filter(|(ch, _)| *ch == channel_num)
+            .map(|(_, name)| name)
+            .collect();
+        channel
+            .outputs()
+            .filter(|name| !referenced.contains(name))
+            .map(utility::name_from_str)
+            .collect()
+    }
+    /// Rebuild the outputs of each channel if the channels have changed
+    ///
+    /// Buses are mixed last so that they have received everything sent to them
+    fn update_outputs(&mut self) {
+        if !self.outputs_dirty {
+            return;
+        }
+        self.outputs = self
+            .channels
+            .iter()
+            .sorted_by_key(|(&channel_num, channel)| (channel.is_bus(), channel_num))
+            .map(|(&channel_num, channel)| {
+                (channel_num, self.channel_outputs(channel_num, channel))
+            })
+            .collect();
+        self.outputs_dirty = false;
+    }
     pub fn resolve_dynamic_value(
         &self,
         dyn_val: &spec::DynamicValue,
@@ -786,6 +1203,17 @@ impl State {
                 .map(|channel| channel.next_from(ch, name, self, cache).left),
         }
     }
+    /// Resolve a dynamic value, falling back to a default if it has no value
+    pub fn resolve_or(
+        &self,
+        dyn_val: &spec::DynamicValue,
+        default: f32,
+        ch: u8,
+        cache: &mut FrameCache,
+    ) -> f32 {
+        self.resolve_dynamic_value(dyn_val, ch, cache)
+            .unwrap_or(default)
+    }
     fn check_cli_commands(&mut self) {
         while let Ok(command) = self.recv.try_recv() {
             let res = self.queue_command(&command);
@@ -986,6 +1414,7 @@ impl Iterator for State {
             .values_mut()
             .filter_map(|lup| lup.controls(state_tempo, loop_period))
             .collect();
+        self.update_outputs();
         // Initialize voice for this frame
         let mut voice = Voice::SILENT;
         // Iterator through the main controls as well as all playing loop controls
@@ -1005,17 +1434,27 @@ impl Iterator for State {
                 audio_input: audio_input.clone(),
                 visited: HashSet::new(),
                 from_loop: i != 0,
+                processed_controls: HashMap::new(),
+                sends: HashMap::new(),
             };
-            // Mix output voices for each channel
+            // Process controls for each channel
             for (&channel_num, channel) in &self.channels {
-                let outputs: Vec<String> = channel.outputs().map(Into::into).collect();
-                for name in outputs {
+                channel.process_controls(channel_num, self, &mut cache);
+            }
+            // Mix output voices for each channel
+            for (channel_num, names) in &self.outputs {
+                let channel = &self.channels[channel_num];
+                for name in names {
                     cache.visited.clear();
-                    voice += channel.next_from(channel_num, &name, self, &mut cache)
+                    voice += channel.next_from(*channel_num, name, self, &mut cache)
                         * self.vars.master_volume;
                 }
             }
         }
+        // Limit the master output
+        if let Some((_, limiter)) = &mut self.limiter {
+            voice = limiter.process(voice);
+        }
         self.frame_queue = Some(voice.right);
         Some(voice.left)
     }
@@ -0,0 +1,328 @@
+use std::{fs, path::Path};
+
+use crate::{library, spec::TuningDef};
+
+/// The frequency of the default reference note
+pub const C4: f32 = 261.63;
+/// The note that plays at the reference frequency by default
+const DEFAULT_REFERENCE_NOTE: i32 = 48;
+
+/// A mapping of notes to scale degrees, as described by a Scala `.kbm` file
+#[derive(Debug, Clone, PartialEq)]
+struct KeyboardMapping {
+    first: i32,
+    last: i32,
+    middle: i32,
+    reference_note: i32,
+    reference_freq: f32,
+    /// The scale degree that is the formal octave. 0 means the scale's period.
+    octave_degree: usize,
+    /// The scale degrees of the keys in one mapping period.
+    /// If this is empty (a map size of 0), every key maps to the next scale degree.
+    keys: Vec<Option<usize>>,
+}
+
+impl Default for KeyboardMapping {
+    fn default() -> Self {
+        KeyboardMapping {
+            first: 0,
+            last: 127,
+            middle: DEFAULT_REFERENCE_NOTE,
+            reference_note: DEFAULT_REFERENCE_NOTE,
+            reference_freq: C4,
+            octave_degree: 0,
+            keys: Vec::new(),
+        }
+    }
+}
+
+/// A musical tuning that maps notes to frequencies
+#[derive(Debug, Clone, PartialEq)]
+pub struct Tuning {
+    /// The cents of each scale degree above the root. The last one is the period.
+    cents: Vec<f32>,
+    mapping: KeyboardMapping,
+}
+
+impl Default for Tuning {
+    fn default() -> Self {
+        Tuning {
+            cents: (1..=12).map(|i| i as f32 * 100.0).collect(),
+            mapping: KeyboardMapping::default(),
+        }
+    }
+}
+
+impl Tuning {
+    /// Load a tuning from its definition
+    pub fn load(def: &TuningDef) -> crate::Result<Self> {
+        let mut tuning = Tuning::default();
+        if let Some(scale) = &def.scale {
+            let path = library::tunings_dir()?.join(scale);
+            tuning.cents = parse_scale(&path, &fs::read_to_string(&path)?)?;
+        }
+        if let Some(keyboard) = &def.keyboard {
+            let path = library::tunings_dir()?.join(keyboard);
+            tuning.mapping = parse_keyboard(&path, &fs::read_to_string(&path)?)?;
+        }
+        if let Some(reference) = def.reference {
+            tuning.mapping.reference_freq = reference;
+        }
+        Ok(tuning)
+    }
+    /// Get the frequency of a possibly fractional note
+    ///
+    /// Returns `0.0` if the note is not mapped
+    pub fn freq(&self, pitch: f32) -> f32 {
+        let low = pitch.floor();
+        let frac = pitch - low;
+        let cents = match (self.cents(low as i32), self.cents(low as i32 + 1)) {
+            (Some(a), Some(b)) => a + (b - a) * frac,
+            (Some(a), None) => a,
+            _ => return 0.0,
+        };
+        let reference_cents = self.cents(self.mapping.reference_note).unwrap_or(0.0);
+        self.mapping.reference_freq * 2_f32.powf((cents - reference_cents) / 1200.0)
+    }
+    /// Get the cents of a note above the middle note
+    fn cents(&self, note: i32) -> Option<f32> {
+        let mapping = &self.mapping;
+        if note < mapping.first || note > mapping.last {
+            return None;
+        }
+        let offset = note - mapping.middle;
+        if mapping.keys.is_empty() {
+            Some(self.degree_cents(offset))
+        } else {
+            let size = mapping.keys.len() as i32;
+            let degree = mapping.keys[offset.rem_euclid(size) as usize]?;
+            let octave = if mapping.octave_degree == 0 {
+                self.period()
+            } else {
+                self.degree_cents(mapping.octave_degree as i32)
+            };
+            Some(offset.div_euclid(size) as f32 * octave + self.degree_cents(degree as i32))
+        }
+    }
+    /// Get the cents of a scale degree, which may lie outside the first period
+    fn degree_cents(&self, degree: i32) -> f32 {
+        let len = self.cents.len() as i32;
+        let periods = degree.div_euclid(len);
+        let index = degree.rem_euclid(len);
+        let within = if index == 0 {
+            0.0
+        } else {
+            self.cents[index as usize - 1]
+        };
+        periods as f32 * self.period() + within
+    }
+    fn period(&self) -> f32 {
+        self.cents.last().copied().unwrap_or(1200.0)
+    }
+}
+
+/// Get the lines of a Scala file that are not comments
+fn scala_lines(text: &str) -> Vec<&str> {
+    text.lines()
+        .filter(|line| !line.starts_with('!'))
+        .map(str::trim)
+        .collect()
+}
+
+fn tuning_error(path: &Path, message: impl Into<String>) -> crate::Error {
+    crate::Error::Tuning(path.into(), message.into())
+}
+
+/// Parse the text of a Scala `.scl` file into a list of cents
+fn parse_scale(path: &Path, text: &str) -> crate::Result<Vec<f32>> {
+    let lines = scala_lines(text);
+    // The first line is the description
+    let count: usize = lines
+        .get(1)
+        .and_then(|line| line.split_whitespace().next())
+        .and_then(|count| count.parse().ok())
+        .ok_or_else(|| tuning_error(path, "Missing note count"))?;
+    let cents = lines
+        .iter()
+        .skip(2)
+        .filter(|line| !line.is_empty())
+        .take(count)
+        .map(|line| {
+            let pitch = line.split_whitespace().next().unwrap_or("");
+            parse_pitch(pitch)
+                .ok_or_else(|| tuning_error(path, format!("Invalid pitch {:?}", pitch)))
+        })
+        .collect::<crate::Result<Vec<f32>>>()?;
+    if cents.len() != count || count == 0 {
+        return Err(tuning_error(
+            path,
+            format!("Expected {} pitches, found {}", count, cents.len()),
+        ));
+    }
+    Ok(cents)
+}
+
+/// Parse a Scala pitch, which is either a number of cents or a ratio
+fn parse_pitch(s: &str) -> Option<f32> {
+    if s.contains('.') {
+        s.parse().ok()
+    } else {
+        let mut parts = s.splitn(2, '/');
+        let num: f32 = parts.next()?.parse().ok()?;
+        let den: f32 = parts.next().map_or(Some(1.0), |den| den.parse().ok())?;
+        if num > 0.0 && den > 0.0 {
+            Some(1200.0 * (num / den).log2())
+        } else {
+            None
+        }
+    }
+}
+
+/// Parse the text of a Scala `.kbm` keyboard mapping file
+fn parse_keyboard(path: &Path, text: &str) -> crate::Result<KeyboardMapping> {
+    let lines = scala_lines(text);
+    let mut fields = lines
+        .iter()
+        .filter(|line| !line.is_empty())
+        .map(|line| line.split_whitespace().next().unwrap_or(""));
+    let mut next = |name: &str| {
+        fields
+            .next()
+            .ok_or_else(|| tuning_error(path, format!("Missing {}", name)))
+    };
+    macro_rules! field {
+        ($name:literal) => {{
+            let field = next($name)?;
+            field
+                .parse()
+                .map_err(|_| tuning_error(path, format!("Invalid {} {:?}", $name, field)))?
+        }};
+    }
+    let size: usize = field!("map size");
+    let mut mapping = KeyboardMapping {
+        first: field!("first note"),
+        last: field!("last note"),
+        middle: field!("middle note"),
+        reference_note: field!("reference note"),
+        reference_freq: field!("reference frequency"),
+        octave_degree: field!("octave degree"),
+        keys: Vec::with_capacity(size),
+    };
+    for _ in 0..size {
+        let key = next("key mapping")?;
+        mapping.keys.push(if key == "x" {
+            None
+        } else {
+            Some(
+                key.parse()
+                    .map_err(|_| tuning_error(path, format!("Invalid key mapping {:?}", key)))?,
+            )
+        });
+    }
+    Ok(mapping)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_freq(tuning: &Tuning, note: f32, freq: f32) {
+        let actual = tuning.freq(note);
+        assert!(
+            (actual - freq).abs() < 0.01,
+            "note {} is {} Hz, expected {} Hz",
+            note,
+            actual,
+            freq
+        );
+    }
+
+    #[test]
+    fn equal_temperament() {
+        let tuning = Tuning::default();
+        assert_freq(&tuning, 48.0, 261.63);
+        assert_freq(&tuning, 57.0, 440.0);
+        assert_freq(&tuning, 45.0, 220.0);
+        assert_freq(&tuning, 60.0, 523.26);
+        assert_freq(&tuning, 48.5, 269.29);
+    }
+
+    #[test]
+    fn scale() {
+        let text = "\
+! just.scl
+!
+Just major triad
+ 4
+!
+ 5/4
+ 3/2
+ 701.955 fifth in cents
+ 2
+";
+        let cents = parse_scale(Path::new("just.scl"), text).unwrap();
+        assert_eq!(cents.len(), 4);
+        assert!((cents[0] - 386.314).abs() < 0.001);
+        assert!((cents[1] - 701.955).abs() < 0.001);
+        assert!((cents[2] - 701.955).abs() < 0.001);
+        assert!((cents[3] - 1200.0).abs() < 0.001);
+        assert!(parse_scale(Path::new("short.scl"), "Short\n 2\n 3/2\n").is_err());
+    }
+
+    #[test]
+    fn keyboard() {
+        let text = "\
+! white.kbm
+! Map size
+7
+! First and last notes
+0
+127
+! Middle note
+60
+! Reference note and frequency
+65
+440.0
+! Octave degree
+12
+! Mapping
+0
+2
+4
+5
+7
+9
+11
+";
+        let mapping = parse_keyboard(Path::new("white.kbm"), text).unwrap();
+        assert_eq!(
+            mapping,
+            KeyboardMapping {
+                first: 0,
+                last: 127,
+                middle: 60,
+                reference_note: 65,
+                reference_freq: 440.0,
+                octave_degree: 12,
+                keys: vec![
+                    Some(0),
+                    Some(2),
+                    Some(4),
+                    Some(5),
+                    Some(7),
+                    Some(9),
+                    Some(11)
+                ],
+            }
+        );
+        // Consecutive keys play a C major scale
+        let tuning = Tuning {
+            mapping,
+            ..Tuning::default()
+        };
+        assert_freq(&tuning, 60.0, 261.63);
+        assert_freq(&tuning, 62.0, 329.63);
+        assert_freq(&tuning, 65.0, 440.0);
+        assert_freq(&tuning, 67.0, 523.25);
+    }
+}
@@ -59,6 +59,14 @@ pub fn name_from_str(s: &str) -> Name {
     Name::from(&s[..s.len().min(NAME_CAPACITY)]).unwrap()
 }
 
+/// Split a node reference like `3:lfo` into its channel and node name
+///
+/// Returns `None` if the reference does not name a channel
+pub fn split_node_ref(s: &str) -> Option<(u8, &str)> {
+    let (channel, name) = s.split_at(s.find(':')?);
+    Some((channel.trim().parse().ok()?, name[1..].trim()))
+}
+
 #[derive(Debug, Clone, Copy, PartialEq, PartialOrd, Serialize, Deserialize)]
 #[serde(transparent)]
 pub struct Float(pub f32);